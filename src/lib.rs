@@ -22,21 +22,43 @@ pub use libproc::proc_pid;
 /// Read messages from the Kernel Message Buffer
 pub use libproc::kmesg_buffer;
 
+#[doc(inline)]
+/// Recognise events such as OOM kills and segfaults in messages from the Kernel Message Buffer
+pub use libproc::kernel_events;
+
 #[doc(inline)]
 /// Get information about resource usage of processes
 pub use libproc::pid_rusage;
 
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+#[doc(inline)]
+/// Get information about the cgroups of processes and their resource usage
+pub use libproc::cgroup;
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+#[doc(inline)]
+/// Get information about file locks held by processes
+pub use libproc::lock_info;
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+#[doc(inline)]
+/// Get information about the mounts visible to processes
+pub use libproc::mount_info;
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+#[doc(inline)]
+/// Get information about the residency of pages of a process's virtual memory
+pub use libproc::page_info;
+
 #[cfg(any(target_os = "macos", doc))]
 #[doc(inline)]
 /// Get information specific to BSD/Darwin on macos
 pub use libproc::bsd_info;
 
-#[cfg(any(target_os = "macos", doc))]
 #[doc(inline)]
 /// Get information about a process's use of different types of file descriptors
 pub use libproc::file_info;
 
-#[cfg(any(target_os = "macos", doc))]
 #[doc(inline)]
 /// Get information about a processes use of network, sockets etc.
 pub use libproc::net_info;
@@ -56,6 +78,11 @@ pub use libproc::thread_info;
 /// Get information about Work Queues
 pub use libproc::work_queue_info;
 
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+#[doc(inline)]
+/// Estimate how much memory processes actively use
+pub use libproc::working_set;
+
 // Not documenting this as this export is legacy, and replaced by all the re-exports of
 // sub-modules above
 #[doc(hidden)]
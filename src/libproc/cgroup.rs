@@ -0,0 +1,647 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, BufRead};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::libproc::mount_info;
+
+/// The usual mount point of the cgroup v2 unified hierarchy
+pub const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+/// A cgroup a process is a member of, from a line of `/proc/<pid>/cgroup`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cgroup {
+    /// Id of the hierarchy, 0 for the cgroup v2 unified hierarchy
+    pub hierarchy_id: u32,
+    /// Controllers bound to the hierarchy, e.g. "cpu", "cpuacct" or "name=systemd" for a
+    /// named hierarchy. Empty for the unified hierarchy
+    pub controllers: Vec<String>,
+    /// Path of the cgroup relative to the root of its hierarchy, e.g.
+    /// "/system.slice/nginx.service"
+    pub path: PathBuf,
+}
+
+impl Cgroup {
+    /// Return true if this is the process's cgroup in the cgroup v2 unified hierarchy
+    #[must_use]
+    pub fn is_unified(&self) -> bool {
+        self.hierarchy_id == 0 && self.controllers.is_empty()
+    }
+}
+
+impl FromStr for Cgroup {
+    type Err = String;
+
+    /// Parse a single line of a `/proc/<pid>/cgroup` file, "hierarchy-ID:controllers:path"
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let error = || format!("Could not parse cgroup line '{line}'");
+        let (hierarchy_id, rest) = line.split_once(':').ok_or_else(error)?;
+        // The path may itself contain ':'
+        let (controllers, path) = rest.split_once(':').ok_or_else(error)?;
+        Ok(Cgroup {
+            hierarchy_id: hierarchy_id.parse().map_err(|_| error())?,
+            controllers: controllers
+                .split(',')
+                .filter(|controller| !controller.is_empty())
+                .map(str::to_owned)
+                .collect(),
+            path: PathBuf::from(path),
+        })
+    }
+}
+
+/// Parse the contents of a `/proc/<pid>/cgroup` file
+///
+/// # Errors
+///
+/// Will return `Err` if the contents cannot be read or a line cannot be parsed
+pub fn parse_proc_cgroup<R: BufRead>(reader: R) -> Result<Vec<Cgroup>, String> {
+    let mut cgroups = Vec::new();
+    for line in reader.lines() {
+        let line = line.map_err(|e| format!("Could not read cgroups: {e}"))?;
+        if !line.trim().is_empty() {
+            cgroups.push(line.parse()?);
+        }
+    }
+    Ok(cgroups)
+}
+
+// Parse the contents of a flat keyed cgroup file, with a "key value" pair on each line
+fn parse_flat_keyed(contents: &str) -> Result<BTreeMap<String, u64>, String> {
+    contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let (key, value) = line
+                .split_once(' ')
+                .ok_or_else(|| format!("Could not parse line '{line}'"))?;
+            let value = value
+                .trim()
+                .parse()
+                .map_err(|_| format!("Could not parse value of '{key}'"))?;
+            Ok((key.to_owned(), value))
+        })
+        .collect()
+}
+
+// Parse a limit, which is either a number or "max" for no limit
+fn parse_limit(contents: &str) -> Result<Option<u64>, String> {
+    match contents.trim() {
+        "max" => Ok(None),
+        value => value
+            .parse()
+            .map(Some)
+            .map_err(|_| format!("Could not parse limit '{value}'")),
+    }
+}
+
+/// Memory usage of a cgroup by type, from `memory.stat`. Amounts of memory are in bytes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryStat {
+    /// Anonymous memory, e.g. heaps and stacks
+    pub anon: u64,
+    /// File backed memory, including the page cache and tmpfs
+    pub file: u64,
+    /// Kernel memory, including stacks, page tables and slabs (Linux 5.18 and later)
+    pub kernel: u64,
+    /// Kernel stacks
+    pub kernel_stack: u64,
+    /// Page tables
+    pub pagetables: u64,
+    /// Network transmission buffers
+    pub sock: u64,
+    /// Shared memory, including tmpfs
+    pub shmem: u64,
+    /// File backed memory mapped with `mmap()`
+    pub file_mapped: u64,
+    /// File backed memory modified but not yet written back
+    pub file_dirty: u64,
+    /// File backed memory being written back
+    pub file_writeback: u64,
+    /// Kernel slab memory
+    pub slab: u64,
+    /// Number of page faults
+    pub pgfault: u64,
+    /// Number of major page faults, which needed I/O
+    pub pgmajfault: u64,
+    /// All the values in the file, including those above, keyed by name
+    pub values: BTreeMap<String, u64>,
+}
+
+impl FromStr for MemoryStat {
+    type Err = String;
+
+    /// Parse the contents of a `memory.stat` file
+    fn from_str(contents: &str) -> Result<Self, Self::Err> {
+        let values = parse_flat_keyed(contents)?;
+        let value = |key: &str| values.get(key).copied().unwrap_or_default();
+        Ok(MemoryStat {
+            anon: value("anon"),
+            file: value("file"),
+            kernel: value("kernel"),
+            kernel_stack: value("kernel_stack"),
+            pagetables: value("pagetables"),
+            sock: value("sock"),
+            shmem: value("shmem"),
+            file_mapped: value("file_mapped"),
+            file_dirty: value("file_dirty"),
+            file_writeback: value("file_writeback"),
+            slab: value("slab"),
+            pgfault: value("pgfault"),
+            pgmajfault: value("pgmajfault"),
+            values,
+        })
+    }
+}
+
+/// Counts of memory events in a cgroup and the cgroups below it, from `memory.events`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryEvents {
+    /// Times memory was reclaimed despite usage being under the `memory.low` protection
+    pub low: u64,
+    /// Times processes were throttled and made to reclaim memory for exceeding `memory.high`
+    pub high: u64,
+    /// Times usage was about to exceed `memory.max`
+    pub max: u64,
+    /// Times usage reached `memory.max` and allocations failed
+    pub oom: u64,
+    /// Number of processes killed by the OOM killer
+    pub oom_kill: u64,
+    /// Times the whole cgroup was killed by the OOM killer (Linux 5.17 and later)
+    pub oom_group_kill: u64,
+}
+
+impl FromStr for MemoryEvents {
+    type Err = String;
+
+    /// Parse the contents of a `memory.events` file
+    fn from_str(contents: &str) -> Result<Self, Self::Err> {
+        let values = parse_flat_keyed(contents)?;
+        let value = |key: &str| values.get(key).copied().unwrap_or_default();
+        Ok(MemoryEvents {
+            low: value("low"),
+            high: value("high"),
+            max: value("max"),
+            oom: value("oom"),
+            oom_kill: value("oom_kill"),
+            oom_group_kill: value("oom_group_kill"),
+        })
+    }
+}
+
+/// CPU usage and throttling of a cgroup, from `cpu.stat`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CpuStat {
+    /// Total CPU time used
+    pub usage: Duration,
+    /// CPU time used in user mode
+    pub user: Duration,
+    /// CPU time used in kernel mode
+    pub system: Duration,
+    /// Number of `cpu.max` enforcement periods that have elapsed, 0 if the cpu controller is
+    /// not enabled
+    pub nr_periods: u64,
+    /// Number of periods in which the cgroup was throttled for reaching its `cpu.max` quota
+    pub nr_throttled: u64,
+    /// Total time the cgroup was throttled for
+    pub throttled: Duration,
+}
+
+impl FromStr for CpuStat {
+    type Err = String;
+
+    /// Parse the contents of a `cpu.stat` file
+    fn from_str(contents: &str) -> Result<Self, Self::Err> {
+        let values = parse_flat_keyed(contents)?;
+        let value = |key: &str| values.get(key).copied().unwrap_or_default();
+        Ok(CpuStat {
+            usage: Duration::from_micros(value("usage_usec")),
+            user: Duration::from_micros(value("user_usec")),
+            system: Duration::from_micros(value("system_usec")),
+            nr_periods: value("nr_periods"),
+            nr_throttled: value("nr_throttled"),
+            throttled: Duration::from_micros(value("throttled_usec")),
+        })
+    }
+}
+
+/// I/O done by a cgroup on one device, from a line of `io.stat`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IoStat {
+    /// Major and minor device numbers of the device
+    pub dev: (u32, u32),
+    /// Bytes read
+    pub rbytes: u64,
+    /// Bytes written
+    pub wbytes: u64,
+    /// Number of read operations
+    pub rios: u64,
+    /// Number of write operations
+    pub wios: u64,
+    /// Bytes discarded
+    pub dbytes: u64,
+    /// Number of discard operations
+    pub dios: u64,
+}
+
+impl FromStr for IoStat {
+    type Err = String;
+
+    /// Parse a single line of an `io.stat` file, e.g. "8:16 rbytes=1459200 wbytes=314773504
+    /// rios=192 wios=353 dbytes=0 dios=0"
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let error = || format!("Could not parse io.stat line '{line}'");
+        let mut fields = line.split_ascii_whitespace();
+        let (major, minor) = fields
+            .next()
+            .and_then(|dev| dev.split_once(':'))
+            .ok_or_else(error)?;
+        let mut stat = IoStat {
+            dev: (
+                major.parse().map_err(|_| error())?,
+                minor.parse().map_err(|_| error())?,
+            ),
+            ..IoStat::default()
+        };
+        for field in fields {
+            let (key, value) = field.split_once('=').ok_or_else(error)?;
+            let value = value.parse().map_err(|_| error())?;
+            match key {
+                "rbytes" => stat.rbytes = value,
+                "wbytes" => stat.wbytes = value,
+                "rios" => stat.rios = value,
+                "wios" => stat.wios = value,
+                "dbytes" => stat.dbytes = value,
+                "dios" => stat.dios = value,
+                _ => {}
+            }
+        }
+        Ok(stat)
+    }
+}
+
+/// A cgroup v2 filesystem mounted at a root directory, through which the resource usage and
+/// limits of the cgroups in it can be read.
+///
+/// Cgroups are identified by their path relative to the root, as in the unified hierarchy
+/// entry returned by [`cgroups`][crate::libproc::proc_pid::cgroups]. Controller files such as
+/// `memory.current` are only present in cgroups with the controller enabled, and not in the
+/// root cgroup.
+///
+/// # Examples
+///
+/// ```
+/// use libproc::libproc::cgroup::CgroupFs;
+/// use libproc::libproc::proc_pid::cgroups;
+/// use std::process;
+///
+/// let cgroupfs = CgroupFs::find().unwrap_or_default();
+/// let own = cgroups(process::id() as i32).expect("Could not get cgroups");
+/// if let Some(unified) = own.iter().find(|cgroup| cgroup.is_unified()) {
+///     if let Ok(memory) = cgroupfs.memory_current(&unified.path) {
+///         println!("The cgroup of this process uses {} bytes of memory", memory);
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CgroupFs {
+    root: PathBuf,
+}
+
+impl Default for CgroupFs {
+    /// A cgroup v2 filesystem mounted at [`CGROUP_ROOT`]
+    fn default() -> Self {
+        CgroupFs::new(CGROUP_ROOT)
+    }
+}
+
+impl CgroupFs {
+    /// A cgroup v2 filesystem mounted at `root`, which may also be a directory laid out like
+    /// one, e.g. for testing
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        CgroupFs { root: root.into() }
+    }
+
+    /// Find where the cgroup v2 filesystem is mounted, from the mounts of this process. This
+    /// is usually [`CGROUP_ROOT`], or `/sys/fs/cgroup/unified` on systems that also use
+    /// cgroup v1 hierarchies.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the mounts cannot be read, or no cgroup v2 filesystem is mounted
+    pub fn find() -> Result<Self, String> {
+        let filename = "/proc/self/mountinfo";
+        let file = fs::File::open(filename)
+            .map_err(|e| format!("Could not open /proc file '{filename}': {e}"))?;
+        mount_info::parse_mountinfo(io::BufReader::new(file))?
+            .into_iter()
+            .find(|mount| mount.fs_type == "cgroup2")
+            .map(|mount| CgroupFs::new(mount.mount_point))
+            .ok_or_else(|| "No cgroup v2 filesystem is mounted".to_string())
+    }
+
+    /// The directory the cgroup filesystem is mounted at
+    #[must_use]
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The directory of the cgroup with path `cgroup`, e.g. "/system.slice/nginx.service"
+    #[must_use]
+    pub fn dir(&self, cgroup: &Path) -> PathBuf {
+        self.root.join(cgroup.strip_prefix("/").unwrap_or(cgroup))
+    }
+
+    fn read(&self, cgroup: &Path, file: &str) -> Result<String, String> {
+        let filename = self.dir(cgroup).join(file);
+        fs::read_to_string(&filename)
+            .map_err(|e| format!("Could not read cgroup file '{}': {e}", filename.display()))
+    }
+
+    fn read_parsed<T: FromStr<Err = String>>(
+        &self,
+        cgroup: &Path,
+        file: &str,
+    ) -> Result<T, String> {
+        self.read(cgroup, file)?
+            .parse()
+            .map_err(|e| format!("{e} in cgroup file '{file}'"))
+    }
+
+    fn read_value(&self, cgroup: &Path, file: &str) -> Result<u64, String> {
+        let contents = self.read(cgroup, file)?;
+        contents
+            .trim()
+            .parse()
+            .map_err(|_| format!("Could not parse the contents of cgroup file '{file}'"))
+    }
+
+    fn read_limit(&self, cgroup: &Path, file: &str) -> Result<Option<u64>, String> {
+        parse_limit(&self.read(cgroup, file)?).map_err(|e| format!("{e} in cgroup file '{file}'"))
+    }
+
+    /// Get the memory used by `cgroup` and the cgroups below it, in bytes, from
+    /// `memory.current`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file cannot be read or parsed, e.g. because the memory
+    /// controller is not enabled for `cgroup`
+    pub fn memory_current(&self, cgroup: &Path) -> Result<u64, String> {
+        self.read_value(cgroup, "memory.current")
+    }
+
+    /// Get the memory limit of `cgroup` in bytes, or `None` if it has no limit, from
+    /// `memory.max`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file cannot be read or parsed
+    pub fn memory_max(&self, cgroup: &Path) -> Result<Option<u64>, String> {
+        self.read_limit(cgroup, "memory.max")
+    }
+
+    /// Get the memory used by `cgroup` by type, from `memory.stat`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file cannot be read or parsed
+    pub fn memory_stat(&self, cgroup: &Path) -> Result<MemoryStat, String> {
+        self.read_parsed(cgroup, "memory.stat")
+    }
+
+    /// Get the counts of memory events, such as OOM kills, in `cgroup`, from `memory.events`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file cannot be read or parsed
+    pub fn memory_events(&self, cgroup: &Path) -> Result<MemoryEvents, String> {
+        self.read_parsed(cgroup, "memory.events")
+    }
+
+    /// Get the CPU usage and throttling of `cgroup`, from `cpu.stat`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file cannot be read or parsed
+    pub fn cpu_stat(&self, cgroup: &Path) -> Result<CpuStat, String> {
+        self.read_parsed(cgroup, "cpu.stat")
+    }
+
+    /// Get the I/O done by `cgroup` on each device, from `io.stat`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file cannot be read or a line cannot be parsed
+    pub fn io_stat(&self, cgroup: &Path) -> Result<Vec<IoStat>, String> {
+        self.read(cgroup, "io.stat")?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(str::parse)
+            .collect()
+    }
+
+    /// Get the number of processes in `cgroup` and the cgroups below it, from `pids.current`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file cannot be read or parsed
+    pub fn pids_current(&self, cgroup: &Path) -> Result<u64, String> {
+        self.read_value(cgroup, "pids.current")
+    }
+
+    /// Get the limit on the number of processes in `cgroup`, or `None` if it has no limit,
+    /// from `pids.max`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file cannot be read or parsed
+    pub fn pids_max(&self, cgroup: &Path) -> Result<Option<u64>, String> {
+        self.read_limit(cgroup, "pids.max")
+    }
+}
+
+#[cfg(test)]
+// Don't worry about wrapping in tests
+#[allow(clippy::cast_possible_wrap)]
+mod test {
+    use super::{parse_proc_cgroup, Cgroup, CgroupFs, CpuStat, IoStat, MemoryEvents};
+    use std::fs;
+    use std::path::Path;
+    use std::time::Duration;
+
+    // A hybrid system, with v1 hierarchies and the v2 unified hierarchy
+    const PROC_CGROUP: &str = "\
+12:cpu,cpuacct:/system.slice/nginx.service
+11:memory:/system.slice/nginx.service
+1:name=systemd:/system.slice/nginx.service
+0::/system.slice/nginx.service
+";
+
+    #[test]
+    fn parse_hybrid_cgroups() {
+        let cgroups = parse_proc_cgroup(PROC_CGROUP.as_bytes()).expect("Could not parse cgroups");
+        assert_eq!(cgroups.len(), 4);
+        assert_eq!(cgroups[0].hierarchy_id, 12);
+        assert_eq!(cgroups[0].controllers, vec!["cpu", "cpuacct"]);
+        assert_eq!(cgroups[0].path, Path::new("/system.slice/nginx.service"));
+        assert!(!cgroups[0].is_unified());
+        assert_eq!(cgroups[2].controllers, vec!["name=systemd"]);
+
+        assert!(cgroups[3].is_unified());
+        assert!(cgroups[3].controllers.is_empty());
+        assert_eq!(cgroups[3].path, Path::new("/system.slice/nginx.service"));
+    }
+
+    #[test]
+    fn parse_path_with_colon() {
+        let cgroup: Cgroup = "0::/user.slice/app:1"
+            .parse()
+            .expect("Could not parse cgroup");
+        assert_eq!(cgroup.path, Path::new("/user.slice/app:1"));
+    }
+
+    #[test]
+    fn parse_invalid_line() {
+        assert!("/system.slice".parse::<Cgroup>().is_err());
+        assert!("x::/".parse::<Cgroup>().is_err());
+    }
+
+    // Create a fake cgroupfs with a service cgroup, using files from a real system
+    fn fake_cgroupfs() -> (tempfile::TempDir, CgroupFs) {
+        let root = tempfile::tempdir().expect("Could not create temp dir");
+        let service = root.path().join("system.slice").join("nginx.service");
+        fs::create_dir_all(&service).expect("Could not create cgroup dir");
+        let files = [
+            ("memory.current", "52391936\n"),
+            ("memory.max", "536870912\n"),
+            (
+                "memory.stat",
+                "anon 18083840\nfile 30191616\nkernel 3690496\nkernel_stack 245760\n\
+                 pagetables 536576\nsock 4096\nshmem 8192\nfile_mapped 11468800\n\
+                 file_dirty 4096\nfile_writeback 0\nanon_thp 0\nslab 2656112\n\
+                 pgfault 118842\npgmajfault 221\n",
+            ),
+            (
+                "memory.events",
+                "low 0\nhigh 0\nmax 12\noom 3\noom_kill 2\noom_group_kill 0\n",
+            ),
+            (
+                "cpu.stat",
+                "usage_usec 8813497\nuser_usec 5217036\nsystem_usec 3596461\n\
+                 core_sched.force_idle_usec 0\nnr_periods 4120\nnr_throttled 87\n\
+                 throttled_usec 2931044\nnr_bursts 0\nburst_usec 0\n",
+            ),
+            (
+                "io.stat",
+                "259:0 rbytes=1459200 wbytes=314773504 rios=192 wios=353 dbytes=0 dios=0\n\
+                 8:16 rbytes=90112 wbytes=0 rios=22 wios=0 dbytes=0 dios=0\n",
+            ),
+            ("pids.current", "17\n"),
+            ("pids.max", "max\n"),
+        ];
+        for (name, contents) in files {
+            fs::write(service.join(name), contents).expect("Could not write cgroup file");
+        }
+        let cgroupfs = CgroupFs::new(root.path());
+        (root, cgroupfs)
+    }
+
+    #[test]
+    fn read_memory() {
+        let (_root, cgroupfs) = fake_cgroupfs();
+        let service = Path::new("/system.slice/nginx.service");
+        assert_eq!(cgroupfs.memory_current(service), Ok(52_391_936));
+        assert_eq!(cgroupfs.memory_max(service), Ok(Some(536_870_912)));
+
+        let stat = cgroupfs.memory_stat(service).expect("memory_stat() failed");
+        assert_eq!(stat.anon, 18_083_840);
+        assert_eq!(stat.file, 30_191_616);
+        assert_eq!(stat.pgmajfault, 221);
+        assert_eq!(stat.values.get("anon_thp"), Some(&0));
+
+        assert_eq!(
+            cgroupfs.memory_events(service),
+            Ok(MemoryEvents {
+                low: 0,
+                high: 0,
+                max: 12,
+                oom: 3,
+                oom_kill: 2,
+                oom_group_kill: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn read_cpu_io_and_pids() {
+        let (_root, cgroupfs) = fake_cgroupfs();
+        let service = Path::new("system.slice/nginx.service");
+        assert_eq!(
+            cgroupfs.cpu_stat(service),
+            Ok(CpuStat {
+                usage: Duration::from_micros(8_813_497),
+                user: Duration::from_micros(5_217_036),
+                system: Duration::from_micros(3_596_461),
+                nr_periods: 4120,
+                nr_throttled: 87,
+                throttled: Duration::from_micros(2_931_044),
+            })
+        );
+
+        let io = cgroupfs.io_stat(service).expect("io_stat() failed");
+        assert_eq!(io.len(), 2);
+        assert_eq!(
+            io[0],
+            IoStat {
+                dev: (259, 0),
+                rbytes: 1_459_200,
+                wbytes: 314_773_504,
+                rios: 192,
+                wios: 353,
+                dbytes: 0,
+                dios: 0,
+            }
+        );
+        assert_eq!(io[1].dev, (8, 16));
+
+        assert_eq!(cgroupfs.pids_current(service), Ok(17));
+        assert_eq!(cgroupfs.pids_max(service), Ok(None));
+    }
+
+    #[test]
+    fn read_missing_and_invalid_files() {
+        let (root, cgroupfs) = fake_cgroupfs();
+        assert!(cgroupfs.memory_current(Path::new("/")).is_err());
+        assert!(cgroupfs
+            .cpu_stat(Path::new("/system.slice/missing.service"))
+            .is_err());
+
+        let service = Path::new("/system.slice/nginx.service");
+        fs::write(cgroupfs.dir(service).join("memory.max"), "lots\n")
+            .expect("Could not write cgroup file");
+        assert!(cgroupfs.memory_max(service).is_err());
+        assert_eq!(
+            cgroupfs.dir(service),
+            root.path().join("system.slice/nginx.service")
+        );
+    }
+
+    #[test]
+    fn read_own_cgroup() {
+        let Ok(cgroupfs) = CgroupFs::find() else {
+            println!("test skipped as no cgroup v2 filesystem is mounted");
+            return;
+        };
+        let own =
+            crate::libproc::proc_pid::cgroups(std::process::id() as i32).expect("cgroups() failed");
+        let unified = own
+            .iter()
+            .find(|cgroup| cgroup.is_unified())
+            .expect("No unified cgroup found");
+        let stat = cgroupfs.cpu_stat(&unified.path).expect("cpu_stat() failed");
+        assert!(stat.usage > Duration::ZERO);
+    }
+}
@@ -1,12 +1,29 @@
+#[cfg(target_os = "macos")]
 use crate::libproc::helpers;
+#[cfg(any(target_os = "macos", doc))]
 use crate::libproc::proc_pid::{ListPIDInfo, PidInfoFlavor};
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+use std::fs;
+#[cfg(target_os = "macos")]
 use std::mem;
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+use std::os::unix::fs::MetadataExt;
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+use std::path::PathBuf;
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+use std::str::FromStr;
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+use std::time::{Duration, Instant};
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+use crate::libproc::proc_pid::file_progress;
 
 #[cfg(target_os = "macos")]
 use crate::osx_libproc_bindings::proc_pidfdinfo;
 #[cfg(target_os = "macos")]
 use libc::c_void;
 
+#[cfg(any(target_os = "macos", doc))]
 /// Flavor of Pid `FileDescriptor` info for different types of File Descriptors
 pub enum PIDFDInfoFlavor {
     /// `VNodeInfo`
@@ -27,9 +44,11 @@ pub enum PIDFDInfoFlavor {
     ATalkInfo = 8,
 }
 
+#[cfg(any(target_os = "macos", doc))]
 /// Struct for Listing File Descriptors
 pub struct ListFDs;
 
+#[cfg(any(target_os = "macos", doc))]
 impl ListPIDInfo for ListFDs {
     type Item = ProcFDInfo;
     fn flavor() -> PidInfoFlavor {
@@ -37,6 +56,7 @@ impl ListPIDInfo for ListFDs {
     }
 }
 
+#[cfg(any(target_os = "macos", doc))]
 /// Struct to hold info about a Processes `FileDescriptor` Info
 #[repr(C)]
 pub struct ProcFDInfo {
@@ -46,6 +66,7 @@ pub struct ProcFDInfo {
     pub proc_fdtype: u32,
 }
 
+#[cfg(any(target_os = "macos", doc))]
 /// Enum for different `FileDescriptor` types
 #[derive(Copy, Clone, Debug)]
 pub enum ProcFDType {
@@ -71,6 +92,7 @@ pub enum ProcFDType {
     Unknown,
 }
 
+#[cfg(any(target_os = "macos", doc))]
 impl From<u32> for ProcFDType {
     fn from(value: u32) -> ProcFDType {
         match value {
@@ -87,6 +109,7 @@ impl From<u32> for ProcFDType {
     }
 }
 
+#[cfg(any(target_os = "macos", doc))]
 /// The `PIDFDInfo` trait is needed for polymorphism on pidfdinfo types, also abstracting flavor
 /// in order to provide type-guaranteed flavor correctness
 pub trait PIDFDInfo: Default {
@@ -170,11 +193,458 @@ pub fn pidfdinfo<T: PIDFDInfo>(pid: i32, fd: i32) -> Result<T, String> {
     }
 }
 
-#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+#[cfg(all(
+    doc,
+    any(target_os = "linux", target_os = "redox", target_os = "android")
+))]
 pub fn pidfdinfo<T: PIDFDInfo>(_pid: i32, _fd: i32) -> Result<T, String> {
     unimplemented!()
 }
 
+// Suffix the kernel appends to the target of a `/proc/<pid>/fd/<fd>` link when the file has
+// been unlinked
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+const DELETED_SUFFIX: &str = " (deleted)";
+
+/// The access mode a file descriptor was opened with
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AccessMode {
+    /// Opened with `O_RDONLY`
+    ReadOnly,
+    /// Opened with `O_WRONLY`
+    WriteOnly,
+    /// Opened with `O_RDWR`
+    ReadWrite,
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+impl From<u32> for AccessMode {
+    fn from(flags: u32) -> AccessMode {
+        // `O_ACCMODE` is a small positive constant
+        #[allow(clippy::cast_sign_loss)]
+        match flags & libc::O_ACCMODE as u32 {
+            0 => AccessMode::ReadOnly,
+            1 => AccessMode::WriteOnly,
+            _ => AccessMode::ReadWrite,
+        }
+    }
+}
+
+/// Information about an open file descriptor, from `/proc/<pid>/fdinfo/<fd>`
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FDInfo {
+    /// Current file offset
+    pub pos: u64,
+    /// Flags the file was opened with (`O_*` values)
+    pub flags: u32,
+    /// Id of the mount containing the file, as in `/proc/<pid>/mountinfo`
+    pub mnt_id: Option<u64>,
+    /// Inode number of the file (Linux 5.3 and later)
+    pub ino: Option<u64>,
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+impl FromStr for FDInfo {
+    type Err = String;
+
+    /// Parse the contents of a `/proc/<pid>/fdinfo/<fd>` file
+    fn from_str(contents: &str) -> Result<Self, Self::Err> {
+        let mut info = FDInfo::default();
+        let mut found_pos = false;
+        for line in contents.lines() {
+            if let Some((name, value)) = line.split_once(':') {
+                let value = value.trim();
+                let error = || format!("Could not parse fdinfo field '{name}' value '{value}'");
+                match name {
+                    "pos" => {
+                        info.pos = value.parse().map_err(|_| error())?;
+                        found_pos = true;
+                    }
+                    "flags" => info.flags = u32::from_str_radix(value, 8).map_err(|_| error())?,
+                    "mnt_id" => info.mnt_id = Some(value.parse().map_err(|_| error())?),
+                    "ino" => info.ino = Some(value.parse().map_err(|_| error())?),
+                    _ => {}
+                }
+            }
+        }
+
+        if found_pos {
+            Ok(info)
+        } else {
+            Err("Could not find 'pos' field in fdinfo".into())
+        }
+    }
+}
+
+/// Get the [`FDInfo`] of file descriptor `fd` of the process with `pid`
+///
+/// # Errors
+///
+/// Will return `Err` if the process or file descriptor does not exist, or its fdinfo file
+/// cannot be read or parsed
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+pub fn fdinfo(pid: i32, fd: i32) -> Result<FDInfo, String> {
+    let filename = format!("/proc/{pid}/fdinfo/{fd}");
+    fs::read_to_string(&filename)
+        .map_err(|e| format!("Could not read /proc file '{filename}': {e}"))?
+        .parse()
+}
+
+/// List the numbers of the file descriptors open in the process with `pid`
+///
+/// # Errors
+///
+/// Will return `Err` if the process does not exist or its fd directory cannot be read
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+pub fn list_fds(pid: i32) -> Result<Vec<i32>, String> {
+    let dirname = format!("/proc/{pid}/fd");
+    let entries =
+        fs::read_dir(&dirname).map_err(|e| format!("Could not read /proc dir '{dirname}': {e}"))?;
+    let mut fds: Vec<i32> = entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.parse().ok())
+        .collect();
+    fds.sort_unstable();
+    Ok(fds)
+}
+
+/// A file open in a process
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenFile {
+    /// File descriptor number
+    pub fd: i32,
+    /// Path the file descriptor resolves to. For descriptors that are not files on a filesystem
+    /// this is a pseudo-path such as `socket:[1234]`, `pipe:[5678]` or `anon_inode:[eventfd]`
+    pub path: PathBuf,
+    /// Access mode the file was opened with
+    pub mode: AccessMode,
+    /// Current file offset
+    pub offset: u64,
+    /// Flags the file was opened with (`O_*` values)
+    pub flags: u32,
+    /// The file has been deleted (unlinked) while still open
+    pub deleted: bool,
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+impl OpenFile {
+    /// Create an `OpenFile` for file descriptor `fd` of the process with the provided pid, from
+    /// the target of its `/proc/<pid>/fd/<fd>` link and its fdinfo.
+    ///
+    /// A file whose own name ends in " (deleted)" has a link target that looks the same as a
+    /// deleted file's, so the file is only considered deleted if it no longer has any links.
+    #[must_use]
+    pub fn new(pid: i32, fd: i32, link: PathBuf, info: &FDInfo) -> OpenFile {
+        let unlinked =
+            || fs::metadata(format!("/proc/{pid}/fd/{fd}")).is_ok_and(|meta| meta.nlink() == 0);
+        let (path, deleted) = match link.to_str().and_then(|l| l.strip_suffix(DELETED_SUFFIX)) {
+            Some(stripped) if link.is_absolute() && unlinked() => (PathBuf::from(stripped), true),
+            _ => (link, false),
+        };
+
+        OpenFile {
+            fd,
+            path,
+            mode: AccessMode::from(info.flags),
+            offset: info.pos,
+            flags: info.flags,
+            deleted,
+        }
+    }
+}
+
+/// How far a process has got through reading or writing a regular file it has open
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileProgress {
+    /// File descriptor number
+    pub fd: i32,
+    /// Path of the file
+    pub path: PathBuf,
+    /// Access mode the file was opened with
+    pub mode: AccessMode,
+    /// Current file offset of the file descriptor
+    pub position: u64,
+    /// Current size of the file in bytes
+    pub size: u64,
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+impl FileProgress {
+    /// Percentage of the file that the file offset has progressed through
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn percent(&self) -> f64 {
+        if self.size == 0 {
+            0.0
+        } else {
+            (self.position.min(self.size) as f64 / self.size as f64) * 100.0
+        }
+    }
+}
+
+/// The throughput of a process through a file and the estimated time for it to reach the end,
+/// calculated from two [`FileProgress`] samples
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileThroughput {
+    /// The most recent progress sample
+    pub progress: FileProgress,
+    /// Bytes per second the file offset advanced between the samples
+    pub bytes_per_sec: f64,
+    /// Estimated time remaining until the offset reaches the end of the file, if it is advancing
+    pub eta: Option<Duration>,
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+impl FileThroughput {
+    /// Calculate the throughput from an `earlier` and a `later` sample taken `elapsed` apart
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn between(earlier: &FileProgress, later: &FileProgress, elapsed: Duration) -> Self {
+        let secs = elapsed.as_secs_f64();
+        let bytes_per_sec = if secs > 0.0 {
+            later.position.saturating_sub(earlier.position) as f64 / secs
+        } else {
+            0.0
+        };
+        let eta = if bytes_per_sec > 0.0 {
+            let remaining = later.size.saturating_sub(later.position) as f64;
+            Some(Duration::from_secs_f64(remaining / bytes_per_sec))
+        } else {
+            None
+        };
+
+        FileThroughput {
+            progress: later.clone(),
+            bytes_per_sec,
+            eta,
+        }
+    }
+}
+
+/// Match up the files in two sets of samples of a process's [`FileProgress`], taken `elapsed`
+/// apart, and calculate the [`FileThroughput`] of each file open in both
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+#[must_use]
+pub fn file_throughput(
+    earlier: &[FileProgress],
+    later: &[FileProgress],
+    elapsed: Duration,
+) -> Vec<FileThroughput> {
+    later
+        .iter()
+        .filter_map(|after| {
+            earlier
+                .iter()
+                .find(|before| before.fd == after.fd && before.path == after.path)
+                .map(|before| FileThroughput::between(before, after, elapsed))
+        })
+        .collect()
+}
+
+/// Samples the progress of a process through the regular files it has open, in the manner of the
+/// `progress` utility, and turns consecutive samples into [`FileThroughput`]s
+///
+/// # Examples
+///
+/// ```
+/// use libproc::libproc::file_info::FileProgressSampler;
+/// use std::process;
+/// use std::thread;
+/// use std::time::Duration;
+///
+/// let pid = process::id() as i32;
+/// let mut sampler = FileProgressSampler::new(pid).expect("Could not sample file progress");
+/// thread::sleep(Duration::from_millis(100));
+/// for file in sampler.sample().expect("Could not sample file progress") {
+///     println!("{}: {:.1}% {:.0} bytes/s, ETA {:?}", file.progress.path.display(),
+///              file.progress.percent(), file.bytes_per_sec, file.eta);
+/// }
+/// ```
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+pub struct FileProgressSampler {
+    pid: i32,
+    last: Vec<FileProgress>,
+    last_time: Instant,
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+impl FileProgressSampler {
+    /// Create a sampler for the process with `pid`, taking the first sample
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the open files of the process cannot be read
+    pub fn new(pid: i32) -> Result<FileProgressSampler, String> {
+        Ok(FileProgressSampler {
+            pid,
+            last: file_progress(pid)?,
+            last_time: Instant::now(),
+        })
+    }
+
+    /// The most recent sample
+    #[must_use]
+    pub fn last(&self) -> &[FileProgress] {
+        &self.last
+    }
+
+    /// Take a new sample, returning the throughput of each file since the previous one
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the open files of the process cannot be read, e.g. it has exited
+    pub fn sample(&mut self) -> Result<Vec<FileThroughput>, String> {
+        let progress = file_progress(self.pid)?;
+        let now = Instant::now();
+        let throughput = file_throughput(&self.last, &progress, now - self.last_time);
+        self.last = progress;
+        self.last_time = now;
+        Ok(throughput)
+    }
+}
+
+#[cfg(all(
+    test,
+    any(target_os = "linux", target_os = "redox", target_os = "android")
+))]
+// Don't worry about wrapping in tests
+#[allow(clippy::cast_possible_wrap)]
+mod linux_test {
+    use super::{
+        fdinfo, file_throughput, AccessMode, FDInfo, FileProgress, FileThroughput, OpenFile,
+    };
+    use std::fs::{self, File};
+    use std::io::Write;
+    use std::os::unix::io::AsRawFd;
+    use std::path::{Path, PathBuf};
+    use std::process;
+    use std::time::Duration;
+
+    fn progress(fd: i32, position: u64, size: u64) -> FileProgress {
+        FileProgress {
+            fd,
+            path: PathBuf::from("/var/backup.tar"),
+            mode: AccessMode::ReadOnly,
+            position,
+            size,
+        }
+    }
+
+    #[test]
+    fn parse_fdinfo() {
+        let info: FDInfo = "pos:\t1024\nflags:\t02100002\nmnt_id:\t26\nino:\t317783\n"
+            .parse()
+            .expect("Could not parse fdinfo");
+        assert_eq!(info.pos, 1024);
+        assert_eq!(info.flags, 0o2_100_002);
+        assert_eq!(info.mnt_id, Some(26));
+        assert_eq!(info.ino, Some(317_783));
+        assert_eq!(AccessMode::from(info.flags), AccessMode::ReadWrite);
+    }
+
+    #[test]
+    fn parse_fdinfo_without_pos() {
+        assert!("flags:\t02\n".parse::<FDInfo>().is_err());
+    }
+
+    #[test]
+    fn access_modes() {
+        assert_eq!(AccessMode::from(0o100_000), AccessMode::ReadOnly);
+        assert_eq!(AccessMode::from(0o2_000_001), AccessMode::WriteOnly);
+        assert_eq!(AccessMode::from(0o2), AccessMode::ReadWrite);
+    }
+
+    // Open a file called `name` in a new temporary directory, returning its link target and fd
+    fn open_temp_file(name: &str) -> (tempfile::TempDir, File, PathBuf) {
+        let dir = tempfile::tempdir().expect("Could not create temp dir");
+        let mut file = File::create(dir.path().join(name)).expect("Could not create file");
+        file.write_all(b"content").expect("Could not write file");
+        let link = fs::read_link(format!("/proc/self/fd/{}", file.as_raw_fd()))
+            .expect("Could not read fd link");
+        (dir, file, link)
+    }
+
+    #[test]
+    fn deleted_open_file() {
+        let (dir, file, _) = open_temp_file("app.log");
+        fs::remove_file(dir.path().join("app.log")).expect("Could not remove file");
+        let fd = file.as_raw_fd();
+        let link = fs::read_link(format!("/proc/self/fd/{fd}")).expect("Could not read fd link");
+        let info = fdinfo(process::id() as i32, fd).expect("Could not get fdinfo");
+
+        let file = OpenFile::new(process::id() as i32, fd, link, &info);
+        assert!(file.deleted);
+        assert_eq!(file.path, dir.path().join("app.log"));
+        assert_eq!(file.mode, AccessMode::WriteOnly);
+        assert_eq!(file.offset, 7);
+    }
+
+    #[test]
+    fn file_named_deleted_not_deleted() {
+        let (dir, file, link) = open_temp_file("x (deleted)");
+        let fd = file.as_raw_fd();
+        let info = fdinfo(process::id() as i32, fd).expect("Could not get fdinfo");
+
+        let file = OpenFile::new(process::id() as i32, fd, link, &info);
+        assert!(!file.deleted);
+        assert_eq!(file.path, dir.path().join("x (deleted)"));
+    }
+
+    #[test]
+    fn pseudo_file_not_deleted() {
+        let file = OpenFile::new(
+            process::id() as i32,
+            4,
+            PathBuf::from("socket:[1234]"),
+            &FDInfo::default(),
+        );
+        assert!(!file.deleted);
+        assert_eq!(file.path, Path::new("socket:[1234]"));
+    }
+
+    #[test]
+    fn progress_percent() {
+        assert!((progress(3, 250, 1000).percent() - 25.0).abs() < f64::EPSILON);
+        assert!(progress(3, 0, 0).percent().abs() < f64::EPSILON);
+        assert!((progress(3, 2000, 1000).percent() - 100.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn throughput_and_eta() {
+        let throughput = FileThroughput::between(
+            &progress(3, 1000, 10_000),
+            &progress(3, 3000, 10_000),
+            Duration::from_secs(2),
+        );
+        assert!((throughput.bytes_per_sec - 1000.0).abs() < f64::EPSILON);
+        assert_eq!(throughput.eta, Some(Duration::from_secs(7)));
+    }
+
+    #[test]
+    fn stalled_has_no_eta() {
+        let throughput = FileThroughput::between(
+            &progress(3, 1000, 10_000),
+            &progress(3, 1000, 10_000),
+            Duration::from_secs(2),
+        );
+        assert_eq!(throughput.eta, None);
+    }
+
+    #[test]
+    fn throughput_matches_fds() {
+        let earlier = [progress(3, 0, 100), progress(4, 0, 100)];
+        let later = [progress(4, 50, 100), progress(5, 10, 100)];
+        let throughput = file_throughput(&earlier, &later, Duration::from_secs(1));
+        assert_eq!(throughput.len(), 1);
+        assert_eq!(throughput[0].progress.fd, 4);
+    }
+}
+
 #[cfg(all(test, target_os = "macos"))]
 mod test {
     use super::pidfdinfo;
@@ -1,8 +1,10 @@
 use errno::errno;
 #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
-use std::fs::File;
+use std::fs::{self, File};
 #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
 use std::io::{BufRead, BufReader};
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+use std::str::FromStr;
 
 /// Helper function to get errno and return a String with the passed in `return_code`, the error
 /// number and a possible message
@@ -58,6 +60,29 @@ pub(crate) fn procfile_field(filename: &str, field_name: &str) -> Result<String,
     ))
 }
 
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// A helper function for reading /proc FS files that contain a single value, such as
+/// `/proc/<pid>/oom_score`
+pub(crate) fn procfile_value<T: FromStr>(filename: &str) -> Result<T, String> {
+    let contents = fs::read_to_string(filename)
+        .map_err(|e| format!("Could not read /proc file '{filename}': {e}"))?;
+    contents
+        .trim()
+        .parse()
+        .map_err(|_| format!("Could not parse the contents of /proc FS file '{filename}'"))
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// Split a device number, as returned by `stat()`, into its major and minor numbers
+pub(crate) fn dev_major_minor(dev: u64) -> (u32, u32) {
+    // Masked to fit into 32 bits, so no truncation
+    #[allow(clippy::cast_possible_truncation)]
+    let major = (((dev >> 32) & 0xffff_f000) | ((dev >> 8) & 0x0000_0fff)) as u32;
+    #[allow(clippy::cast_possible_truncation)]
+    let minor = (((dev >> 12) & 0xffff_ff00) | (dev & 0x0000_00ff)) as u32;
+    (major, minor)
+}
+
 #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
 /// Parse a memory amount string into an integer number of bytes
 /// e.g., 220,844kB -->
@@ -91,7 +116,14 @@ mod test {
 
     #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
     mod linux {
-        use crate::libproc::helpers::parse_memory_string;
+        use crate::libproc::helpers::{dev_major_minor, parse_memory_string};
+
+        #[test]
+        fn test_dev_major_minor() {
+            assert_eq!(dev_major_minor(0xfe00), (0xfe, 0));
+            assert_eq!(dev_major_minor(0x0801), (8, 1));
+            assert_eq!(dev_major_minor(0x1000_5672_3489), (0x1234, 0x5_6789));
+        }
 
         #[test]
         fn test_valid_memory_string() {
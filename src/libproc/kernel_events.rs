@@ -0,0 +1,679 @@
+use std::time::Duration;
+
+use crate::libproc::kmesg_buffer::KmsgRecord;
+
+/// A process killed by the OOM killer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OomKill {
+    /// The pid of the process killed
+    pub pid: u32,
+    /// The command name of the process killed
+    pub comm: String,
+    /// The uid of the process killed, if reported
+    pub uid: Option<u32>,
+    /// Total virtual memory of the process, in bytes
+    pub total_vm: u64,
+    /// Resident anonymous memory of the process, in bytes
+    pub anon_rss: u64,
+    /// Resident file backed memory of the process, in bytes
+    pub file_rss: u64,
+    /// Resident shared memory of the process, in bytes
+    pub shmem_rss: u64,
+    /// The `oom_score_adj` of the process, if reported
+    pub oom_score_adj: Option<i32>,
+    /// The memory cgroup of the process, if reported, e.g. `/system.slice/nginx.service`
+    pub memcg: Option<String>,
+    /// The constraint that ran out of memory, if reported, e.g. `CONSTRAINT_MEMCG`
+    pub constraint: Option<String>,
+    /// The kill was caused by a memory cgroup reaching its limit, not the whole system
+    /// running out of memory
+    pub cgroup_limit: bool,
+}
+
+impl OomKill {
+    /// Total resident memory of the process when it was killed, in bytes
+    #[must_use]
+    pub fn rss(&self) -> u64 {
+        self.anon_rss + self.file_rss + self.shmem_rss
+    }
+}
+
+/// The type of fault that killed a process
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FaultKind {
+    /// Access to an invalid address
+    Segfault {
+        /// The address accessed
+        address: u64,
+    },
+    /// A general protection fault, e.g. use of a non-canonical address
+    GeneralProtection,
+    /// Another trap, e.g. "invalid opcode" or "divide error"
+    Trap(String),
+}
+
+/// A process killed by a fault, such as a segfault or general protection fault
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fault {
+    /// The type of fault
+    pub kind: FaultKind,
+    /// The pid of the process
+    pub pid: u32,
+    /// The command name of the process
+    pub comm: String,
+    /// The instruction pointer at the fault
+    pub ip: u64,
+    /// The stack pointer at the fault
+    pub sp: u64,
+    /// The architecture specific error code of the fault
+    pub error: u64,
+    /// The name of the executable or library the instruction pointer was in, if known
+    pub library: Option<String>,
+    /// The offset of the instruction pointer in `library`
+    pub offset: Option<u64>,
+}
+
+/// A task that has been blocked in uninterruptible sleep for too long
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HungTask {
+    /// The pid of the task
+    pub pid: u32,
+    /// The command name of the task
+    pub comm: String,
+    /// How long the task had been blocked when it was reported
+    pub blocked: Duration,
+}
+
+/// A CPU that has been stuck running in the kernel without scheduling for too long
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SoftLockup {
+    /// The CPU that was stuck
+    pub cpu: u32,
+    /// How long the CPU had been stuck when it was reported
+    pub stuck: Duration,
+    /// The pid of the task running on the CPU
+    pub pid: u32,
+    /// The command name of the task running on the CPU
+    pub comm: String,
+}
+
+/// An I/O error reported by the block layer or a file system buffer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IoError {
+    /// The device the error was on, e.g. `sda` or `nvme0n1p2`
+    pub device: String,
+    /// The type of error, e.g. `I/O`, `critical medium` or `Buffer I/O`
+    pub error: String,
+    /// The sector of the device the error was at, if reported
+    pub sector: Option<u64>,
+    /// The logical block of the device the error was at, if reported
+    pub block: Option<u64>,
+    /// The operation that failed, if reported, e.g. `READ` or `async page read`
+    pub operation: Option<String>,
+}
+
+/// An event recognised in the kernel log
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KernelEvent {
+    /// A process was killed by the OOM killer
+    OomKill(OomKill),
+    /// A process was killed by a fault
+    Fault(Fault),
+    /// A task was blocked for too long
+    HungTask(HungTask),
+    /// A CPU was stuck for too long
+    SoftLockup(SoftLockup),
+    /// An I/O error on a disk
+    IoError(IoError),
+}
+
+// Details of an OOM kill reported before the "Killed process" line
+#[derive(Debug, Default)]
+struct OomContext {
+    memcg: Option<String>,
+    constraint: Option<String>,
+    pid: Option<u32>,
+    cgroup_limit: bool,
+}
+
+/// Classifies kernel log records into [`KernelEvent`]s.
+///
+/// Some events, such as OOM kills, are reported over several records, so records should be
+/// passed to [`KernelEventClassifier::classify`] in the order they were logged.
+#[derive(Debug, Default)]
+pub struct KernelEventClassifier {
+    oom: Option<OomContext>,
+}
+
+impl KernelEventClassifier {
+    /// Create a classifier
+    #[must_use]
+    pub fn new() -> Self {
+        KernelEventClassifier::default()
+    }
+
+    /// Return the event reported by `record`, or `None` if it is not the record that completes
+    /// a recognised event
+    pub fn classify(&mut self, record: &KmsgRecord) -> Option<KernelEvent> {
+        let message = record.message.as_str();
+        if let Some(details) = message.strip_prefix("oom-kill:") {
+            self.oom = Some(parse_oom_kill_details(details));
+            return None;
+        }
+        if let Some(task) = message.strip_prefix("Task in ") {
+            // Older kernels: "Task in /foo killed as a result of limit of /foo"
+            if let Some((memcg, _)) = task.split_once(" killed as a result of limit of ") {
+                let oom = self.oom.get_or_insert_with(OomContext::default);
+                oom.memcg = Some(memcg.to_string());
+                oom.cgroup_limit = true;
+            }
+            return None;
+        }
+        if message.starts_with("Memory cgroup out of memory:") {
+            self.oom
+                .get_or_insert_with(OomContext::default)
+                .cgroup_limit = true;
+        }
+        if let Some(kill) = parse_killed_process(message) {
+            let context = self.oom.take().unwrap_or_default();
+            if context.pid.is_none_or(|pid| pid == kill.pid) {
+                return Some(KernelEvent::OomKill(OomKill {
+                    memcg: context.memcg,
+                    constraint: context.constraint,
+                    cgroup_limit: context.cgroup_limit,
+                    ..kill
+                }));
+            }
+            return Some(KernelEvent::OomKill(OomKill {
+                cgroup_limit: context.cgroup_limit,
+                ..kill
+            }));
+        }
+
+        parse_fault(message)
+            .map(KernelEvent::Fault)
+            .or_else(|| parse_hung_task(message).map(KernelEvent::HungTask))
+            .or_else(|| parse_soft_lockup(message).map(KernelEvent::SoftLockup))
+            .or_else(|| parse_io_error(message).map(KernelEvent::IoError))
+    }
+}
+
+/// Return the events recognised in `records`, with the record that completed each one
+///
+/// # Examples
+///
+/// ```
+/// use libproc::kernel_events::{classify, KernelEvent};
+/// use libproc::kmesg_buffer::parse_kmsg;
+///
+/// let dump = "3,2,5000000,-;INFO: task jbd2/sda1-8:312 blocked for more than 120 seconds.\n";
+/// let records = parse_kmsg(dump.as_bytes()).expect("Could not parse records");
+/// for (record, event) in classify(&records) {
+///     if let KernelEvent::HungTask(task) = event {
+///         println!("{:?}: {} ({}) hung", record.timestamp, task.comm, task.pid);
+///     }
+/// }
+/// ```
+pub fn classify<'a, I>(records: I) -> Vec<(&'a KmsgRecord, KernelEvent)>
+where
+    I: IntoIterator<Item = &'a KmsgRecord>,
+{
+    let mut classifier = KernelEventClassifier::new();
+    records
+        .into_iter()
+        .filter_map(|record| classifier.classify(record).map(|event| (record, event)))
+        .collect()
+}
+
+fn parse_hex(value: &str) -> Option<u64> {
+    u64::from_str_radix(value.trim_start_matches("0x"), 16).ok()
+}
+
+// Split "comm[pid]" into its parts
+fn parse_comm_pid(task: &str) -> Option<(String, u32)> {
+    let (comm, pid) = task.strip_suffix(']')?.rsplit_once('[')?;
+    Some((comm.to_string(), pid.parse().ok()?))
+}
+
+// Split "comm:pid" into its parts
+fn parse_comm_colon_pid(task: &str) -> Option<(String, u32)> {
+    let (comm, pid) = task.rsplit_once(':')?;
+    Some((comm.to_string(), pid.parse().ok()?))
+}
+
+// "constraint=CONSTRAINT_MEMCG,nodemask=(null),cpuset=/,mems_allowed=0,oom_memcg=/foo,
+//  task_memcg=/foo,task=stress,pid=1234,uid=0"
+fn parse_oom_kill_details(details: &str) -> OomContext {
+    let mut context = OomContext::default();
+    for field in details.split(',') {
+        match field.split_once('=') {
+            Some(("constraint", value)) => context.constraint = Some(value.to_string()),
+            Some(("task_memcg", value)) => context.memcg = Some(value.to_string()),
+            Some(("pid", value)) => context.pid = value.parse().ok(),
+            _ => {}
+        }
+    }
+    context
+}
+
+fn parse_kb(value: &str) -> Option<u64> {
+    value
+        .strip_suffix("kB")?
+        .parse::<u64>()
+        .ok()
+        .map(|kb| kb * 1024)
+}
+
+// "Out of memory: Killed process 1234 (stress) total-vm:1056000kB, anon-rss:524000kB,
+//  file-rss:1200kB, shmem-rss:0kB, UID:0 pgtables:1100kB oom_score_adj:0"
+fn parse_killed_process(message: &str) -> Option<OomKill> {
+    let (_, killed) = message.split_once("Killed process ")?;
+    let (pid, rest) = killed.split_once(" (")?;
+    let (comm, fields) = rest.rsplit_once(") total-vm:")?;
+    let mut kill = OomKill {
+        pid: pid.parse().ok()?,
+        comm: comm.to_string(),
+        uid: None,
+        total_vm: 0,
+        anon_rss: 0,
+        file_rss: 0,
+        shmem_rss: 0,
+        oom_score_adj: None,
+        memcg: None,
+        constraint: None,
+        cgroup_limit: false,
+    };
+    let fields = format!("total-vm:{fields}");
+    for field in fields.split(|c: char| c == ',' || c.is_whitespace()) {
+        match field.split_once(':') {
+            Some(("total-vm", value)) => kill.total_vm = parse_kb(value)?,
+            Some(("anon-rss", value)) => kill.anon_rss = parse_kb(value)?,
+            Some(("file-rss", value)) => kill.file_rss = parse_kb(value)?,
+            Some(("shmem-rss", value)) => kill.shmem_rss = parse_kb(value)?,
+            Some(("UID", value)) => kill.uid = value.parse().ok(),
+            Some(("oom_score_adj", value)) => kill.oom_score_adj = value.parse().ok(),
+            _ => {}
+        }
+    }
+    Some(kill)
+}
+
+// The " in libc.so.6[7f1c2e400000+195000]" after a fault, or on newer kernels
+// " in libc.so.6[5b6c,7f1c2e400000+195000]" with the offset first
+fn parse_fault_location(location: &str, ip: u64) -> (Option<String>, Option<u64>) {
+    let Some(location) = location.split_whitespace().next() else {
+        return (None, None);
+    };
+    let Some((library, vma)) = location
+        .strip_suffix(']')
+        .and_then(|location| location.rsplit_once('['))
+    else {
+        return (None, None);
+    };
+    let offset = match vma.split_once(',') {
+        Some((offset, _)) => parse_hex(offset),
+        None => vma
+            .split_once('+')
+            .and_then(|(base, _)| parse_hex(base))
+            .and_then(|base| ip.checked_sub(base)),
+    };
+    (Some(library.to_string()), offset)
+}
+
+// "stress[1234]: segfault at 0 ip 000055d5c3a1b1c9 sp 00007ffd2b0c8d40 error 6 in
+//  stress[55d5c3a1b000+1000]"
+// "traps: stress[1234] general protection fault ip:7f1c2e4a5b6c sp:7ffd4c3b2a10 error:0 in
+//  libc.so.6[7f1c2e400000+195000]"
+// "traps: stress[1234] trap invalid opcode ip:55d5c3a1b1c9 sp:7ffd2b0c8d40 error:0 in
+//  stress[55d5c3a1b000+1000]"
+fn parse_fault(message: &str) -> Option<Fault> {
+    let (task, kind, registers) = if let Some((task, rest)) = message.split_once(": segfault at ") {
+        let (address, registers) = rest.split_once(' ')?;
+        let kind = FaultKind::Segfault {
+            address: parse_hex(address)?,
+        };
+        (task, kind, registers)
+    } else {
+        let trap = message.strip_prefix("traps: ")?;
+        let (head, registers) = trap.split_once(" ip:")?;
+        if let Some(task) = head.strip_suffix(" general protection fault") {
+            (task, FaultKind::GeneralProtection, registers)
+        } else {
+            let (task, name) = head.rsplit_once("] trap ")?;
+            (
+                &head[..=task.len()],
+                FaultKind::Trap(name.to_string()),
+                registers,
+            )
+        }
+    };
+    let (comm, pid) = parse_comm_pid(task)?;
+
+    // Segfaults use "ip X sp Y error Z", traps "X sp:Y error:Z" after the "ip:"
+    let registers = registers
+        .trim_start_matches("ip ")
+        .replace(" sp:", " sp ")
+        .replace(" error:", " error ");
+    let mut words = registers.split_whitespace();
+    let ip = parse_hex(words.next()?)?;
+    if words.next()? != "sp" {
+        return None;
+    }
+    let sp = parse_hex(words.next()?)?;
+    if words.next()? != "error" {
+        return None;
+    }
+    let error = parse_hex(words.next()?)?;
+    let (library, offset) = match words.next() {
+        Some("in") => parse_fault_location(&words.collect::<Vec<_>>().join(" "), ip),
+        _ => (None, None),
+    };
+
+    Some(Fault {
+        kind,
+        pid,
+        comm,
+        ip,
+        sp,
+        error,
+        library,
+        offset,
+    })
+}
+
+// "INFO: task jbd2/sda1-8:312 blocked for more than 120 seconds."
+fn parse_hung_task(message: &str) -> Option<HungTask> {
+    let task = message.strip_prefix("INFO: task ")?;
+    let (task, blocked) = task.split_once(" blocked for more than ")?;
+    let (comm, pid) = parse_comm_colon_pid(task)?;
+    let seconds = blocked.strip_suffix(" seconds.")?.parse().ok()?;
+    Some(HungTask {
+        pid,
+        comm,
+        blocked: Duration::from_secs(seconds),
+    })
+}
+
+// "watchdog: BUG: soft lockup - CPU#3 stuck for 22s! [stress:1234]"
+fn parse_soft_lockup(message: &str) -> Option<SoftLockup> {
+    let (_, lockup) = message.split_once("BUG: soft lockup - CPU#")?;
+    let (cpu, rest) = lockup.split_once(" stuck for ")?;
+    let (seconds, task) = rest.split_once("s! [")?;
+    let (comm, pid) = parse_comm_colon_pid(task.strip_suffix(']')?)?;
+    Some(SoftLockup {
+        cpu: cpu.parse().ok()?,
+        stuck: Duration::from_secs(seconds.parse().ok()?),
+        pid,
+        comm,
+    })
+}
+
+// "blk_update_request: I/O error, dev sda, sector 2048 op 0x0:(READ) flags 0x0 phys_seg 1
+//  prio class 0", or without the "blk_update_request: " on newer kernels
+// "Buffer I/O error on dev sda1, logical block 0, async page read"
+fn parse_io_error(message: &str) -> Option<IoError> {
+    if let Some(buffer) = message.strip_prefix("Buffer I/O error on dev ") {
+        let mut fields = buffer.split(", ");
+        let device = fields.next()?.to_string();
+        let block = fields
+            .next()
+            .and_then(|field| field.strip_prefix("logical block "))
+            .and_then(|block| block.parse().ok());
+        return Some(IoError {
+            device,
+            error: "Buffer I/O".to_string(),
+            sector: None,
+            block,
+            operation: fields.next().map(str::to_string),
+        });
+    }
+
+    let message = message
+        .strip_prefix("blk_update_request: ")
+        .unwrap_or(message);
+    let (error, rest) = message.split_once(" error, dev ")?;
+    let (device, rest) = rest.split_once(", sector ")?;
+    let (sector, rest) = rest.split_once(' ').unwrap_or((rest, ""));
+    let operation = rest
+        .split_once(":(")
+        .and_then(|(_, op)| op.split_once(')'))
+        .map(|(op, _)| op.to_string());
+    Some(IoError {
+        device: device.to_string(),
+        error: error.to_string(),
+        sector: sector.parse().ok(),
+        block: None,
+        operation,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{
+        classify, Fault, FaultKind, HungTask, IoError, KernelEvent, KernelEventClassifier, OomKill,
+        SoftLockup,
+    };
+    use crate::libproc::kmesg_buffer::parse_kmsg;
+
+    fn events(dump: &str) -> Vec<KernelEvent> {
+        let records = parse_kmsg(dump.as_bytes()).expect("Could not parse records");
+        classify(&records)
+            .into_iter()
+            .map(|(_, event)| event)
+            .collect()
+    }
+
+    #[test]
+    fn memcg_oom_kill() {
+        let dump = "\
+4,1001,93422118,-;stress invoked oom-killer: gfp_mask=0xcc0(GFP_KERNEL), order=0, oom_score_adj=500
+6,1002,93422200,-;memory: usage 524288kB, limit 524288kB, failcnt 1207
+6,1003,93422260,-;oom-kill:constraint=CONSTRAINT_MEMCG,nodemask=(null),cpuset=/,mems_allowed=0,oom_memcg=/system.slice/stress.service,task_memcg=/system.slice/stress.service,task=stress,pid=48213,uid=0
+3,1004,93422301,-;Memory cgroup out of memory: Killed process 48213 (stress) total-vm:1056000kB, anon-rss:523264kB, file-rss:1200kB, shmem-rss:0kB, UID:0 pgtables:1100kB oom_score_adj:500
+6,1005,93422990,-;oom_reaper: reaped process 48213 (stress), now anon-rss:0kB, file-rss:0kB, shmem-rss:0kB
+";
+        let kill = OomKill {
+            pid: 48213,
+            comm: "stress".to_string(),
+            uid: Some(0),
+            total_vm: 1_056_000 * 1024,
+            anon_rss: 523_264 * 1024,
+            file_rss: 1200 * 1024,
+            shmem_rss: 0,
+            oom_score_adj: Some(500),
+            memcg: Some("/system.slice/stress.service".to_string()),
+            constraint: Some("CONSTRAINT_MEMCG".to_string()),
+            cgroup_limit: true,
+        };
+        assert_eq!(kill.rss(), 524_464 * 1024);
+        assert_eq!(events(dump), vec![KernelEvent::OomKill(kill)]);
+    }
+
+    #[test]
+    fn system_oom_kill() {
+        let dump = "\
+6,2201,7254118220,-;oom-kill:constraint=CONSTRAINT_NONE,nodemask=(null),cpuset=/,mems_allowed=0,global_oom,task_memcg=/user.slice/user-1000.slice/session-2.scope,task=Web Content,pid=9120,uid=1000
+3,2202,7254118301,-;Out of memory: Killed process 9120 (Web Content) total-vm:3321228kB, anon-rss:1823532kB, file-rss:0kB, shmem-rss:41236kB, UID:1000 pgtables:5876kB oom_score_adj:167
+";
+        match events(dump).as_slice() {
+            [KernelEvent::OomKill(kill)] => {
+                assert_eq!(kill.pid, 9120);
+                assert_eq!(kill.comm, "Web Content");
+                assert_eq!(kill.uid, Some(1000));
+                assert_eq!(kill.oom_score_adj, Some(167));
+                assert_eq!(
+                    kill.memcg.as_deref(),
+                    Some("/user.slice/user-1000.slice/session-2.scope")
+                );
+                assert!(!kill.cgroup_limit);
+            }
+            other => panic!("Unexpected events {:?}", other),
+        }
+    }
+
+    #[test]
+    fn old_kernel_oom_kill() {
+        let dump = "\
+6,510,3011220000,-;Task in /docker/3f2a killed as a result of limit of /docker/3f2a
+3,511,3011230000,-;Memory cgroup out of memory: Kill process 2817 (java) score 1000 or sacrifice child
+3,512,3011230100,-;Killed process 2817 (java) total-vm:4515064kB, anon-rss:1046512kB, file-rss:15488kB, shmem-rss:0kB
+";
+        match events(dump).as_slice() {
+            [KernelEvent::OomKill(kill)] => {
+                assert_eq!(kill.pid, 2817);
+                assert_eq!(kill.comm, "java");
+                assert_eq!(kill.memcg.as_deref(), Some("/docker/3f2a"));
+                assert_eq!(kill.oom_score_adj, None);
+                assert_eq!(kill.uid, None);
+                assert!(kill.cgroup_limit);
+            }
+            other => panic!("Unexpected events {:?}", other),
+        }
+    }
+
+    #[test]
+    fn segfaults() {
+        let dump = "\
+6,3001,1532012000,-;crashy[20481]: segfault at 0 ip 000055d5c3a1b1c9 sp 00007ffd2b0c8d40 error 6 in crashy[55d5c3a1b000+1000]
+6,3002,1532012001,-;Code: 00 00 00 c3 66 2e 0f 1f 84 00 00 00 00 00 90 f3 0f 1e fa 55 48 89 e5
+6,3003,1633101000,-;python3[31337]: segfault at 7f1c2e5ff000 ip 00007f1c2e4a5b6c sp 00007ffd4c3b2a10 error 4 in libc.so.6[a5b6c,7f1c2e400000+195000] likely on CPU 2 (core 2, socket 0)
+";
+        assert_eq!(
+            events(dump),
+            vec![
+                KernelEvent::Fault(Fault {
+                    kind: FaultKind::Segfault { address: 0 },
+                    pid: 20481,
+                    comm: "crashy".to_string(),
+                    ip: 0x55d5_c3a1_b1c9,
+                    sp: 0x7ffd_2b0c_8d40,
+                    error: 6,
+                    library: Some("crashy".to_string()),
+                    offset: Some(0x1c9),
+                }),
+                KernelEvent::Fault(Fault {
+                    kind: FaultKind::Segfault {
+                        address: 0x7f1c_2e5f_f000
+                    },
+                    pid: 31337,
+                    comm: "python3".to_string(),
+                    ip: 0x7f1c_2e4a_5b6c,
+                    sp: 0x7ffd_4c3b_2a10,
+                    error: 4,
+                    library: Some("libc.so.6".to_string()),
+                    offset: Some(0xa_5b6c),
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn traps() {
+        let dump = "\
+6,4100,2100000000,-;traps: node[4410] general protection fault ip:7f1c2e4a5b6c sp:7ffd4c3b2a10 error:0 in libc.so.6[7f1c2e400000+195000]
+6,4101,2100500000,-;traps: a.out[4412] trap invalid opcode ip:401126 sp:7ffe1e7c5d10 error:0 in a.out[401000+1000]
+";
+        assert_eq!(
+            events(dump),
+            vec![
+                KernelEvent::Fault(Fault {
+                    kind: FaultKind::GeneralProtection,
+                    pid: 4410,
+                    comm: "node".to_string(),
+                    ip: 0x7f1c_2e4a_5b6c,
+                    sp: 0x7ffd_4c3b_2a10,
+                    error: 0,
+                    library: Some("libc.so.6".to_string()),
+                    offset: Some(0xa_5b6c),
+                }),
+                KernelEvent::Fault(Fault {
+                    kind: FaultKind::Trap("invalid opcode".to_string()),
+                    pid: 4412,
+                    comm: "a.out".to_string(),
+                    ip: 0x40_1126,
+                    sp: 0x7ffe_1e7c_5d10,
+                    error: 0,
+                    library: Some("a.out".to_string()),
+                    offset: Some(0x126),
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn hung_tasks_and_lockups() {
+        let dump = "\
+3,5001,245760123,-;INFO: task jbd2/sda1-8:312 blocked for more than 120 seconds.
+3,5002,245760130,-;      Not tainted 6.1.0-18-amd64 #1 Debian 6.1.76-1
+3,5003,245760135,-;\"echo 0 > /proc/sys/kernel/hung_task_timeout_secs\" disables this message.
+0,5004,380112000,-;watchdog: BUG: soft lockup - CPU#3 stuck for 22s! [kworker/3:1:8821]
+";
+        assert_eq!(
+            events(dump),
+            vec![
+                KernelEvent::HungTask(HungTask {
+                    pid: 312,
+                    comm: "jbd2/sda1-8".to_string(),
+                    blocked: Duration::from_secs(120),
+                }),
+                KernelEvent::SoftLockup(SoftLockup {
+                    cpu: 3,
+                    stuck: Duration::from_secs(22),
+                    pid: 8821,
+                    comm: "kworker/3:1".to_string(),
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn io_errors() {
+        let dump = "\
+3,6001,901200000,-;blk_update_request: I/O error, dev sdb, sector 1953520000 op 0x0:(READ) flags 0x80700 phys_seg 1 prio class 0
+3,6002,901200100,-;Buffer I/O error on dev sdb1, logical block 244190000, async page read
+3,6003,902000000,-;critical medium error, dev nvme0n1, sector 419430400 op 0x1:(WRITE) flags 0x8800 phys_seg 32 prio class 2
+";
+        assert_eq!(
+            events(dump),
+            vec![
+                KernelEvent::IoError(IoError {
+                    device: "sdb".to_string(),
+                    error: "I/O".to_string(),
+                    sector: Some(1_953_520_000),
+                    block: None,
+                    operation: Some("READ".to_string()),
+                }),
+                KernelEvent::IoError(IoError {
+                    device: "sdb1".to_string(),
+                    error: "Buffer I/O".to_string(),
+                    sector: None,
+                    block: Some(244_190_000),
+                    operation: Some("async page read".to_string()),
+                }),
+                KernelEvent::IoError(IoError {
+                    device: "nvme0n1".to_string(),
+                    error: "critical medium".to_string(),
+                    sector: Some(419_430_400),
+                    block: None,
+                    operation: Some("WRITE".to_string()),
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn unrelated_records() {
+        let dump = "\
+6,1,0,-;Linux version 6.1.0-18-amd64 (debian-kernel@lists.debian.org)
+6,2,1000,-;e1000e 0000:00:1f.6 eno1: NIC Link is Up 1000 Mbps Full Duplex
+";
+        assert!(events(dump).is_empty());
+        let records = parse_kmsg(dump.as_bytes()).expect("Could not parse records");
+        let mut classifier = KernelEventClassifier::new();
+        assert!(records
+            .iter()
+            .all(|record| classifier.classify(record).is_none()));
+    }
+}
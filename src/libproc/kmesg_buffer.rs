@@ -1,23 +1,903 @@
-#[cfg(target_os = "macos")]
+use std::fmt;
+use std::io::BufRead;
 use std::str;
+use std::str::FromStr;
+use std::time::Duration;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::time::SystemTime;
 
 #[cfg(target_os = "macos")]
 use libc::c_void;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use libc::{c_char, c_int};
 
 #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
 use std::fs::File;
 #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
-use std::io::{BufRead, BufReader};
+use std::fs::OpenOptions;
 #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
-use std::sync::mpsc;
+use std::io::{self, Read, Seek, SeekFrom, Write};
 #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
-use std::sync::mpsc::Receiver;
+use std::os::unix::fs::OpenOptionsExt;
 #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
-use std::{thread, time};
+use std::os::unix::io::AsRawFd;
 
 #[cfg(target_os = "macos")]
 use crate::osx_libproc_bindings::{proc_kmsgbuf, MAXBSIZE as MAX_MSG_BSIZE};
 
+/// The severity of a kernel log message, as used by `printk()` and `syslog()`
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogLevel {
+    /// System is unusable
+    Emergency = 0,
+    /// Action must be taken immediately
+    Alert = 1,
+    /// Critical conditions
+    Critical = 2,
+    /// Error conditions
+    Error = 3,
+    /// Warning conditions
+    Warning = 4,
+    /// Normal but significant condition
+    Notice = 5,
+    /// Informational
+    Info = 6,
+    /// Debug-level messages
+    Debug = 7,
+}
+
+impl From<u8> for LogLevel {
+    /// Convert the lowest 3 bits of a syslog priority into a level
+    fn from(value: u8) -> LogLevel {
+        match value & 7 {
+            0 => LogLevel::Emergency,
+            1 => LogLevel::Alert,
+            2 => LogLevel::Critical,
+            3 => LogLevel::Error,
+            4 => LogLevel::Warning,
+            5 => LogLevel::Notice,
+            6 => LogLevel::Info,
+            _ => LogLevel::Debug,
+        }
+    }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            LogLevel::Emergency => "emerg",
+            LogLevel::Alert => "alert",
+            LogLevel::Critical => "crit",
+            LogLevel::Error => "err",
+            LogLevel::Warning => "warn",
+            LogLevel::Notice => "notice",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A record from the kernel message buffer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KmsgRecord {
+    /// Syslog facility, 0 for messages from the kernel itself
+    pub facility: u8,
+    /// Severity of the message
+    pub level: LogLevel,
+    /// Sequence number of the record, which increases by one for each record logged
+    pub sequence: u64,
+    /// Time since boot at which the message was logged, from the kernel's local clock. This is
+    /// not one of the POSIX clocks and does not advance while the system is suspended, which is
+    /// what `BootClock` corrects for when converting it to wall-clock time on Linux
+    pub timestamp: Duration,
+    /// The record is a fragment of a message continued across records
+    pub continuation: bool,
+    /// The message text
+    pub message: String,
+    /// Key/value pairs describing the source of the message, e.g. `("DEVICE", "b8:0")`
+    pub dict: Vec<(String, String)>,
+}
+
+// Non-printable bytes (and '\') in records are escaped as "\xNN"
+fn unescape(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut unescaped = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' && bytes.get(i + 1) == Some(&b'x') {
+            if let Some(byte) = field
+                .get(i + 2..i + 4)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                unescaped.push(byte);
+                i += 4;
+                continue;
+            }
+        }
+        unescaped.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&unescaped).into_owned()
+}
+
+impl FromStr for KmsgRecord {
+    type Err = String;
+
+    /// Parse a record in the `/dev/kmsg` format: a "faclev,seqnum,timestamp,flag;message" line
+    /// followed by zero or more " KEY=value" dictionary lines
+    fn from_str(record: &str) -> Result<Self, Self::Err> {
+        let error = || format!("Could not parse kernel message record '{record}'");
+        let mut lines = record.lines();
+        let (header, message) = lines
+            .next()
+            .and_then(|line| line.split_once(';'))
+            .ok_or_else(error)?;
+
+        let mut fields = header.split(',');
+        let priority: u32 = fields
+            .next()
+            .and_then(|f| f.parse().ok())
+            .ok_or_else(error)?;
+        let sequence = fields
+            .next()
+            .and_then(|f| f.parse().ok())
+            .ok_or_else(error)?;
+        let timestamp = fields
+            .next()
+            .and_then(|f| f.parse().ok())
+            .ok_or_else(error)?;
+        // The flag was added in Linux 3.5, and further fields may be added in future
+        let continuation = matches!(fields.next(), Some("c" | "+"));
+
+        let dict = lines
+            .filter_map(|line| line.strip_prefix(' ')?.split_once('='))
+            .map(|(key, value)| (key.to_owned(), unescape(value)))
+            .collect();
+
+        #[allow(clippy::cast_possible_truncation)]
+        Ok(KmsgRecord {
+            facility: (priority >> 3) as u8,
+            level: LogLevel::from((priority & 7) as u8),
+            sequence,
+            timestamp: Duration::from_micros(timestamp),
+            continuation,
+            message: unescape(message),
+            dict,
+        })
+    }
+}
+
+/// Parse kernel message records in the `/dev/kmsg` format (as also written by
+/// `cat /dev/kmsg > dump`) from `reader`, so that saved dumps can be analysed offline
+///
+/// # Errors
+///
+/// Will return `Err` if the contents cannot be read or a record cannot be parsed
+///
+/// # Examples
+///
+/// ```
+/// use libproc::kmesg_buffer::{parse_kmsg, LogLevel};
+///
+/// let dump = "6,339,5140900,-;NET: Registered protocol family 10\n";
+/// let records = parse_kmsg(dump.as_bytes()).expect("Could not parse records");
+/// assert_eq!(records[0].level, LogLevel::Info);
+/// assert_eq!(records[0].message, "NET: Registered protocol family 10");
+/// ```
+pub fn parse_kmsg<R: BufRead>(reader: R) -> Result<Vec<KmsgRecord>, String> {
+    let mut records = Vec::new();
+    let mut record = String::new();
+
+    for line in reader.lines() {
+        let line = line.map_err(|e| format!("Could not read kernel messages: {e}"))?;
+        // Dictionary lines start with a space and belong to the preceding record
+        if !line.starts_with(' ') && !record.is_empty() {
+            records.push(record.parse()?);
+            record.clear();
+        }
+        if !line.trim().is_empty() {
+            record.push_str(&line);
+            record.push('\n');
+        }
+    }
+    if !record.is_empty() {
+        records.push(record.parse()?);
+    }
+
+    Ok(records)
+}
+
+/// A filter selecting kernel message records by level, facility and time. An empty filter
+/// selects all records, and each condition added narrows the selection.
+///
+/// # Examples
+///
+/// ```
+/// use libproc::kmesg_buffer::{parse_kmsg, KmsgFilter, LogLevel};
+/// use std::time::Duration;
+///
+/// let dump = "3,1,1000000,-;disk error\n6,2,2000000,-;all good\n3,3,9000000,-;late error\n";
+/// let records = parse_kmsg(dump.as_bytes()).expect("Could not parse records");
+/// let filter = KmsgFilter::new()
+///     .level(LogLevel::Warning)
+///     .until(Duration::from_secs(5));
+/// let errors = filter.apply(records);
+/// assert_eq!(errors.len(), 1);
+/// assert_eq!(errors[0].message, "disk error");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KmsgFilter {
+    level: Option<LogLevel>,
+    facilities: Vec<u8>,
+    since: Option<Duration>,
+    until: Option<Duration>,
+    // Set by an `until_time` before boot, which no record can have been logged at or before
+    until_before_boot: bool,
+}
+
+impl KmsgFilter {
+    /// Create a filter that selects all records
+    #[must_use]
+    pub fn new() -> Self {
+        KmsgFilter::default()
+    }
+
+    /// Select records of `level` or more severe, e.g. `LogLevel::Warning` selects warnings,
+    /// errors and worse
+    #[must_use]
+    pub fn level(mut self, level: LogLevel) -> Self {
+        self.level = Some(level);
+        self
+    }
+
+    /// Select records of `facility` (0 is the kernel). May be called more than once to select
+    /// several facilities
+    #[must_use]
+    pub fn facility(mut self, facility: u8) -> Self {
+        self.facilities.push(facility);
+        self
+    }
+
+    /// Select records logged at or after `timestamp`, as time since boot
+    #[must_use]
+    pub fn since(mut self, timestamp: Duration) -> Self {
+        self.since = Some(timestamp);
+        self
+    }
+
+    /// Select records logged at or before `timestamp`, as time since boot
+    #[must_use]
+    pub fn until(mut self, timestamp: Duration) -> Self {
+        self.until = Some(timestamp);
+        self.until_before_boot = false;
+        self
+    }
+
+    /// Return true if `record` is selected by the filter
+    #[must_use]
+    pub fn matches(&self, record: &KmsgRecord) -> bool {
+        self.level.is_none_or(|level| record.level <= level)
+            && (self.facilities.is_empty() || self.facilities.contains(&record.facility))
+            && self.since.is_none_or(|since| record.timestamp >= since)
+            && self.until.is_none_or(|until| record.timestamp <= until)
+            && !self.until_before_boot
+    }
+
+    /// Return the `records` selected by the filter
+    pub fn apply<I: IntoIterator<Item = KmsgRecord>>(&self, records: I) -> Vec<KmsgRecord> {
+        records
+            .into_iter()
+            .filter(|record| self.matches(record))
+            .collect()
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn clock_time(clock: libc::clockid_t) -> Option<Duration> {
+    let mut time = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    if unsafe { libc::clock_gettime(clock, std::ptr::addr_of_mut!(time)) } != 0 {
+        return None;
+    }
+    #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+    Some(Duration::new(time.tv_sec as u64, time.tv_nsec as u32))
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+/// Converts between the timestamps of kernel message records and wall-clock time.
+///
+/// Record timestamps are taken from a monotonic clock that does not advance while the system is
+/// suspended, so adding them to the boot time gives a wall-clock time that is early by the
+/// total time spent suspended. When `CLOCK_BOOTTIME`, which does include suspended time, is
+/// available this drift is measured and corrected for. The correction is exact for messages
+/// logged since the last resume, older messages are still early by the time spent in later
+/// suspends.
+///
+/// # Examples
+///
+/// ```
+/// use libproc::kmesg_buffer::{kmsg_records, BootClock};
+///
+/// let clock = BootClock::now().expect("Could not read clocks");
+/// if let Ok(records) = kmsg_records() {
+///     for record in records {
+///         println!("{:?} {}", clock.to_system_time(record.timestamp), record.message);
+///     }
+/// }
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BootClock {
+    boot_time: SystemTime,
+    suspended: Duration,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl BootClock {
+    /// Measure the boot time and time spent suspended, from the current time of the clocks
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the system clocks cannot be read
+    pub fn now() -> Result<Self, String> {
+        let realtime = SystemTime::now();
+        let monotonic = clock_time(libc::CLOCK_MONOTONIC).ok_or_else(|| {
+            format!(
+                "Could not read CLOCK_MONOTONIC: {}",
+                io::Error::last_os_error()
+            )
+        })?;
+        let boottime = clock_time(libc::CLOCK_BOOTTIME).unwrap_or(monotonic);
+        Ok(BootClock::new(
+            realtime - boottime,
+            boottime.saturating_sub(monotonic),
+        ))
+    }
+
+    /// Create a clock from the wall-clock `boot_time` and the total time spent `suspended`
+    /// since boot
+    #[must_use]
+    pub fn new(boot_time: SystemTime, suspended: Duration) -> Self {
+        BootClock {
+            boot_time,
+            suspended,
+        }
+    }
+
+    /// The wall-clock time the system booted
+    #[must_use]
+    pub fn boot_time(&self) -> SystemTime {
+        self.boot_time
+    }
+
+    /// The total time the system has spent suspended since boot
+    #[must_use]
+    pub fn suspended(&self) -> Duration {
+        self.suspended
+    }
+
+    /// Convert the timestamp of a record into wall-clock time
+    #[must_use]
+    pub fn to_system_time(&self, timestamp: Duration) -> SystemTime {
+        self.boot_time + self.suspended + timestamp
+    }
+
+    /// Convert a wall-clock time into a record timestamp, or `None` if it is before the
+    /// (suspend corrected) boot time
+    #[must_use]
+    pub fn to_timestamp(&self, time: SystemTime) -> Option<Duration> {
+        time.duration_since(self.boot_time + self.suspended).ok()
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl KmsgFilter {
+    /// Select records logged at or after the wall-clock time `time`, converted with `clock`
+    #[must_use]
+    pub fn since_time(self, clock: &BootClock, time: SystemTime) -> Self {
+        self.since(clock.to_timestamp(time).unwrap_or_default())
+    }
+
+    /// Select records logged at or before the wall-clock time `time`, converted with `clock`.
+    /// If `time` is before boot no records are selected
+    #[must_use]
+    pub fn until_time(mut self, clock: &BootClock, time: SystemTime) -> Self {
+        if let Some(timestamp) = clock.to_timestamp(time) {
+            return self.until(timestamp);
+        }
+        self.until = None;
+        self.until_before_boot = true;
+        self
+    }
+}
+
+// Parse a line in the `syslog(2)` format: "<priority>[seconds.micros] message", where the
+// timestamp is only present if the kernel was configured to print it
+fn parse_syslog_line(line: &str, sequence: u64) -> Option<KmsgRecord> {
+    let (priority, rest) = line.strip_prefix('<')?.split_once('>')?;
+    let priority: u32 = priority.parse().ok()?;
+    let (timestamp, message) = match rest.strip_prefix('[').and_then(|rest| rest.split_once(']')) {
+        Some((timestamp, message)) => {
+            let (secs, micros) = timestamp.trim().split_once('.')?;
+            let timestamp = Duration::from_secs(secs.parse().ok()?)
+                + Duration::from_micros(micros.parse().ok()?);
+            (timestamp, message.strip_prefix(' ').unwrap_or(message))
+        }
+        None => (Duration::ZERO, rest),
+    };
+
+    #[allow(clippy::cast_possible_truncation)]
+    Some(KmsgRecord {
+        facility: (priority >> 3) as u8,
+        level: LogLevel::from((priority & 7) as u8),
+        sequence,
+        timestamp,
+        continuation: false,
+        message: message.to_owned(),
+        dict: Vec::new(),
+    })
+}
+
+/// Parse kernel messages in the `syslog(2)` format returned by `klogctl()` and `dmesg -r`,
+/// i.e. "<priority>[seconds.micros] message" lines, into records.
+///
+/// This format has no sequence numbers, so records are numbered in the order they are read
+/// starting at 0, and has no dictionaries or continuation flags.
+///
+/// # Errors
+///
+/// Will return `Err` if the contents cannot be read or a line cannot be parsed
+///
+/// # Examples
+///
+/// ```
+/// use libproc::kmesg_buffer::{parse_syslog, LogLevel};
+/// use std::time::Duration;
+///
+/// let dump = "<3>[   12.345678] ata1: link is slow to respond\n";
+/// let records = parse_syslog(dump.as_bytes()).expect("Could not parse records");
+/// assert_eq!(records[0].level, LogLevel::Error);
+/// assert_eq!(records[0].timestamp, Duration::from_micros(12_345_678));
+/// assert_eq!(records[0].message, "ata1: link is slow to respond");
+/// ```
+pub fn parse_syslog<R: BufRead>(reader: R) -> Result<Vec<KmsgRecord>, String> {
+    let mut records = Vec::new();
+    for line in reader.lines() {
+        let line = line.map_err(|e| format!("Could not read kernel messages: {e}"))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let sequence = records.len() as u64;
+        records.push(
+            parse_syslog_line(&line, sequence)
+                .ok_or_else(|| format!("Could not parse kernel message '{line}'"))?,
+        );
+    }
+    Ok(records)
+}
+
+// Actions of `klogctl()`, see syslog(2)
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const SYSLOG_ACTION_READ_ALL: c_int = 3;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const SYSLOG_ACTION_CLEAR: c_int = 5;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const SYSLOG_ACTION_CONSOLE_LEVEL: c_int = 8;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const SYSLOG_ACTION_SIZE_BUFFER: c_int = 10;
+
+// Call `klogctl()` with an action that takes no buffer
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn klogctl_action(action: c_int, len: c_int) -> Result<(), String> {
+    if unsafe { libc::klogctl(action, std::ptr::null_mut(), len) } < 0 {
+        Err(format!("klogctl failed: {}", io::Error::last_os_error()))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+/// Read the whole kernel ring buffer with `klogctl(SYSLOG_ACTION_READ_ALL)`, in the
+/// `syslog(2)` format that can be parsed with [`parse_syslog`]
+///
+/// # Errors
+///
+/// An `Err` will be returned if the caller does not have permission to read the kernel log
+/// (`CAP_SYSLOG`, or `CAP_SYS_ADMIN`, when `kernel.dmesg_restrict` is set)
+pub fn klog_read_all() -> Result<String, String> {
+    let size = unsafe { libc::klogctl(SYSLOG_ACTION_SIZE_BUFFER, std::ptr::null_mut(), 0) };
+    if size < 0 {
+        return Err(format!(
+            "Could not get the size of the kernel ring buffer: {}",
+            io::Error::last_os_error()
+        ));
+    }
+
+    // `size` is not negative here - so cannot lose the sign
+    #[allow(clippy::cast_sign_loss)]
+    let mut buffer = vec![0u8; size as usize];
+    let len = unsafe {
+        libc::klogctl(
+            SYSLOG_ACTION_READ_ALL,
+            buffer.as_mut_ptr().cast::<c_char>(),
+            size,
+        )
+    };
+    if len < 0 {
+        return Err(format!(
+            "Could not read the kernel ring buffer: {}",
+            io::Error::last_os_error()
+        ));
+    }
+    #[allow(clippy::cast_sign_loss)]
+    buffer.truncate(len as usize);
+    Ok(String::from_utf8_lossy(&buffer).into_owned())
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+/// Read the records in the kernel ring buffer with `klogctl()`. Unlike [`kmsg_records`] the
+/// records have no sequence numbers or dictionaries, see [`parse_syslog`].
+///
+/// # Errors
+///
+/// An `Err` will be returned if the kernel log cannot be read, see [`klog_read_all`]
+pub fn klog_records() -> Result<Vec<KmsgRecord>, String> {
+    parse_syslog(klog_read_all()?.as_bytes())
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+/// Clear the kernel ring buffer with `klogctl(SYSLOG_ACTION_CLEAR)`. This requires `CAP_SYSLOG`.
+///
+/// # Errors
+///
+/// An `Err` will be returned if the caller does not have permission to clear the buffer
+pub fn klog_clear() -> Result<(), String> {
+    klogctl_action(SYSLOG_ACTION_CLEAR, 0)
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+/// Set the level of the messages printed to the console with
+/// `klogctl(SYSLOG_ACTION_CONSOLE_LEVEL)`: messages of `level` and those more severe are
+/// printed. This requires `CAP_SYSLOG`.
+///
+/// # Errors
+///
+/// An `Err` will be returned if the caller does not have permission to set the console level
+pub fn klog_set_console_level(level: LogLevel) -> Result<(), String> {
+    // Messages with a level below the console log level are printed
+    klogctl_action(SYSLOG_ACTION_CONSOLE_LEVEL, level as c_int + 1)
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// Read the records in the kernel message buffer from `/dev/kmsg`, or if it cannot be opened
+/// with `klogctl()` (see [`klog_records`])
+///
+/// # Errors
+///
+/// An `Err` will be returned if the kernel message buffer cannot be read by either method, or a
+/// record cannot be parsed
+///
+/// # Examples
+///
+/// ```
+/// use libproc::kmesg_buffer::kmsg_records;
+///
+/// match kmsg_records() {
+///     Ok(records) => for record in records {
+///         println!("[{:?}] {}: {}", record.timestamp, record.level, record.message);
+///     },
+///     Err(err) => eprintln!("Error: {}", err)
+/// }
+/// ```
+pub fn kmsg_records() -> Result<Vec<KmsgRecord>, String> {
+    match open_nonblocking(DEV_KMSG) {
+        Ok(file) => read_available_records(file)?
+            .iter()
+            .map(|record| record.parse())
+            .collect(),
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        Err(_) => klog_records(),
+        #[cfg(target_os = "redox")]
+        Err(e) => Err(format!("Could not open {DEV_KMSG}: {e}")),
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+const DEV_KMSG: &str = "/dev/kmsg";
+
+// Each read of /dev/kmsg returns one record, and fails if the buffer cannot hold it. Records are
+// limited to 8KiB of text by the kernel, plus their dictionary
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+const KMSG_RECORD_MAX: usize = 16 * 1024;
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+// The longest record, including its `<N>` priority prefix and newline, that `/dev/kmsg` accepts in one
+// write. Newer kernels accept up to 1024 bytes but older ones reject anything over 992
+const KMSG_WRITE_MAX: usize = 992;
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+// The facility of messages logged from user space
+const LOG_USER: u8 = 1;
+
+// Split `message` into the records to write to `/dev/kmsg` to log it at `level`: one per line,
+// with lines too long for a single record split on character boundaries. Each record ends with
+// a newline, otherwise the kernel holds it back waiting for a continuation
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+fn kmsg_write_records(level: LogLevel, message: &str) -> Vec<String> {
+    let prefix = format!("<{}>", (LOG_USER << 3) | level as u8);
+    let max_text = KMSG_WRITE_MAX - prefix.len() - 1;
+    let mut records = Vec::new();
+    for mut line in message.trim_end_matches('\n').split('\n') {
+        loop {
+            let mut end = line.len().min(max_text);
+            while !line.is_char_boundary(end) {
+                end -= 1;
+            }
+            records.push(format!("{prefix}{}\n", &line[..end]));
+            line = &line[end..];
+            if line.is_empty() {
+                break;
+            }
+        }
+    }
+    records
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// Write `message` to the kernel log at `level`, with the user facility, e.g. to mark points
+/// in a script that can be correlated with kernel messages.
+///
+/// Each line of `message` is logged as a separate record, and lines longer than the kernel
+/// accepts in one record are split over several records. The kernel may rate limit messages
+/// from user space, see `printk_devkmsg` in `sysctl kernel`.
+///
+/// # Errors
+///
+/// Will return `Err` if `/dev/kmsg` cannot be opened, usually because the process lacks the
+/// permission, or a record cannot be written
+///
+/// # Examples
+///
+/// ```
+/// use libproc::kmesg_buffer::{self, LogLevel};
+///
+/// if kmesg_buffer::write(LogLevel::Notice, "provisioning: network configured").is_err() {
+///     println!("Could not write to the kernel log, it needs to be run as root");
+/// }
+/// ```
+pub fn write(level: LogLevel, message: &str) -> Result<(), String> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .open(DEV_KMSG)
+        .map_err(|e| format!("Could not open {DEV_KMSG}: {e}"))?;
+    for record in kmsg_write_records(level, message) {
+        // Each write must be one whole record, so a short write cannot be completed later
+        match file.write(record.as_bytes()) {
+            Ok(len) if len == record.len() => {}
+            Ok(len) => {
+                return Err(format!(
+                    "Only {len} of {} bytes of a kernel log record were written",
+                    record.len()
+                ))
+            }
+            Err(e) => return Err(format!("Could not write to {DEV_KMSG}: {e}")),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// Where a [`KmsgReader`] starts reading the kernel message buffer
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum KmsgStart {
+    /// The oldest record still in the buffer
+    Beginning,
+    /// After the newest record, so only messages logged from now on are read
+    End,
+    /// The record with this sequence number, e.g. one more than the last record seen by a
+    /// previous reader
+    Sequence(u64),
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// An entry read from the kernel message buffer by a [`KmsgReader`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KmsgEntry {
+    /// A record
+    Record(KmsgRecord),
+    /// This many records were overwritten in the ring buffer before they could be read
+    Lost(u64),
+}
+
+// Detect gaps in the sequence numbers of the records read, caused by the ring buffer wrapping
+// around before they were read
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+#[derive(Debug, Default)]
+struct SequenceTracker {
+    next: Option<u64>,
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+impl SequenceTracker {
+    // Record that the record with `sequence` was read, returning how many were missed before it
+    fn track(&mut self, sequence: u64) -> u64 {
+        let lost = self.next.map_or(0, |next| sequence.saturating_sub(next));
+        self.next = Some(sequence + 1);
+        lost
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// A reader of records from the kernel message buffer (`/dev/kmsg`), which can follow new
+/// messages as they are logged.
+///
+/// As an [`Iterator`] it blocks waiting for the next record, while [`KmsgReader::try_next`]
+/// returns immediately if there is none. If the ring buffer wraps around and overwrites
+/// records before they are read, a [`KmsgEntry::Lost`] entry reports how many were lost.
+///
+/// # Examples
+///
+/// ```
+/// use libproc::kmesg_buffer::{KmsgEntry, KmsgReader, KmsgStart};
+///
+/// if let Ok(mut reader) = KmsgReader::open(KmsgStart::Beginning) {
+///     while let Ok(Some(entry)) = reader.try_next() {
+///         match entry {
+///             KmsgEntry::Record(record) => println!("{}: {}", record.level, record.message),
+///             KmsgEntry::Lost(count) => println!("{} messages lost", count),
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct KmsgReader {
+    file: File,
+    sequence: SequenceTracker,
+    skip_before: u64,
+    pending: Option<KmsgRecord>,
+    buffer: Vec<u8>,
+    failed: bool,
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+impl KmsgReader {
+    /// Open the kernel message buffer for reading, starting at `start`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `/dev/kmsg` cannot be opened, usually because the caller does not
+    /// have permission (see `kernel.dmesg_restrict`)
+    pub fn open(start: KmsgStart) -> Result<Self, String> {
+        // Reads never block, so that `try_next` can skip records without waiting for new ones
+        let mut file =
+            open_nonblocking(DEV_KMSG).map_err(|e| format!("Could not open {DEV_KMSG}: {e}"))?;
+        let mut sequence = SequenceTracker::default();
+        let mut skip_before = 0;
+        match start {
+            KmsgStart::Beginning => {}
+            KmsgStart::End => {
+                file.seek(SeekFrom::End(0))
+                    .map_err(|e| format!("Could not seek to the end of {DEV_KMSG}: {e}"))?;
+            }
+            KmsgStart::Sequence(start) => {
+                sequence.next = Some(start);
+                skip_before = start;
+            }
+        }
+
+        Ok(KmsgReader {
+            file,
+            sequence,
+            skip_before,
+            pending: None,
+            buffer: vec![0; KMSG_RECORD_MAX],
+            failed: false,
+        })
+    }
+
+    /// The sequence number of the next record expected, once a record has been read or if
+    /// reading started at a sequence number. This can be used to resume reading later.
+    #[must_use]
+    pub fn next_sequence(&self) -> Option<u64> {
+        self.sequence.next
+    }
+
+    /// Return the next entry if one is available without blocking, or `None` if there is none
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `/dev/kmsg` cannot be read or a record cannot be parsed
+    pub fn try_next(&mut self) -> Result<Option<KmsgEntry>, String> {
+        self.read_entry(false)
+    }
+
+    // Wait until there is a record to read
+    fn wait(&mut self) -> Result<(), String> {
+        let mut poll_fd = libc::pollfd {
+            fd: self.file.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        if unsafe { libc::poll(std::ptr::addr_of_mut!(poll_fd), 1, -1) } < 0 {
+            let e = io::Error::last_os_error();
+            if e.kind() != io::ErrorKind::Interrupted {
+                self.failed = true;
+                return Err(format!("Could not poll {DEV_KMSG}: {e}"));
+            }
+        }
+        Ok(())
+    }
+
+    // Read one record, retrying if interrupted or if the record to be read was overwritten.
+    // If there is none, wait for one if `block` is set, otherwise return `None`
+    fn read_record(&mut self, block: bool) -> Result<Option<KmsgRecord>, String> {
+        loop {
+            match self.file.read(&mut self.buffer) {
+                Ok(len) => {
+                    let record = str::from_utf8(&self.buffer[..len]).map_err(|_| {
+                        "Could not convert kernel message record from utf8".to_string()
+                    })?;
+                    return record.parse().map(Some);
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    if !block {
+                        return Ok(None);
+                    }
+                    self.wait()?;
+                }
+                // The ring buffer wrapped around, the next read returns the oldest record left
+                Err(e) if e.raw_os_error() == Some(libc::EPIPE) => {}
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    self.failed = true;
+                    return Err(format!("Could not read {DEV_KMSG}: {e}"));
+                }
+            }
+        }
+    }
+
+    // Read the next entry, skipping records before the starting sequence number. If there is
+    // none, wait for one if `block` is set, otherwise return `None`
+    fn read_entry(&mut self, block: bool) -> Result<Option<KmsgEntry>, String> {
+        if let Some(record) = self.pending.take() {
+            return Ok(Some(KmsgEntry::Record(record)));
+        }
+
+        let record = loop {
+            let Some(record) = self.read_record(block)? else {
+                return Ok(None);
+            };
+            if record.sequence >= self.skip_before {
+                break record;
+            }
+        };
+        let lost = self.sequence.track(record.sequence);
+        if lost > 0 {
+            self.pending = Some(record);
+            Ok(Some(KmsgEntry::Lost(lost)))
+        } else {
+            Ok(Some(KmsgEntry::Record(record)))
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+impl Iterator for KmsgReader {
+    type Item = Result<KmsgEntry, String>;
+
+    /// Return the next entry, blocking until a message is logged if there is none. Once
+    /// reading `/dev/kmsg` has failed the error is returned and the iteration ends.
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        // A blocking read only returns once there is an entry
+        self.read_entry(true).transpose()
+    }
+}
+
 #[cfg(any(target_os = "macos", doc))]
 /// Read messages from the kernel message buffer
 ///
@@ -27,10 +907,9 @@ use crate::osx_libproc_bindings::{proc_kmsgbuf, MAXBSIZE as MAX_MSG_BSIZE};
 /// See <http://opensource.apple.com//source/system_cmds/system_cmds-336.6/dmesg.tproj/dmesg.c>
 ///
 /// On linux:
-/// Turns out that reading to the end of an "infinite file" like "/dev/kmsg" with standard file
-/// reading methods will block at the end of file, so a workaround is required. Do the blocking
-/// reads on a thread that sends lines read back through a channel, and then return when the thread
-/// has blocked and can't send anymore. Returning will end the thread and the channel.
+/// Reading past the last record of "/dev/kmsg" blocks until a new message is logged, so it is
+/// opened with `O_NONBLOCK` and one whole record is read at a time, until a read fails with
+/// `EAGAIN` as all the records in the buffer have been read.
 ///
 /// # Errors
 ///
@@ -66,47 +945,390 @@ pub fn kmsgbuf() -> Result<String, String> {
 
 #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
 pub fn kmsgbuf() -> Result<String, String> {
-    let mut file = File::open("/dev/kmsg");
-    if file.is_err() {
-        file = File::open("/dev/console");
-    }
-    let file = file.map_err(|_| "Could not open /dev/kmsg nor /dev/console file '{}'")?;
-    let kmsg_channel = spawn_kmsg_channel(file);
-    let duration = time::Duration::from_millis(1);
-    let mut buf = String::new();
-    while let Ok(line) = kmsg_channel.recv_timeout(duration) {
-        buf.push_str(&line);
+    match open_nonblocking(DEV_KMSG) {
+        Ok(file) => Ok(read_available_records(file)?.concat()),
+        // This is in the `syslog(2)` format rather than the `/dev/kmsg` format
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        Err(_) => klog_read_all(),
+        #[cfg(target_os = "redox")]
+        Err(e) => Err(format!("Could not open {DEV_KMSG}: {e}")),
     }
+}
 
-    Ok(buf)
+// Open a file so that reads fail with `EAGAIN` instead of blocking when there is nothing to read
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+fn open_nonblocking(path: &str) -> io::Result<File> {
+    OpenOptions::new()
+        .read(true)
+        .custom_flags(libc::O_NONBLOCK)
+        .open(path)
 }
 
-// Create a channel to return lines read from a file on, then create a thread that reads the lines
-// and sends them back on the channel one by one. Eventually it will get to EOF or block
+// Read all the records available from a file opened with `open_nonblocking()`. Each read of
+// `/dev/kmsg` returns exactly one whole record, until there are none left and it fails with
+// `EAGAIN`
 #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
-fn spawn_kmsg_channel(file: File) -> Receiver<String> {
-    let mut reader = BufReader::new(file);
-    let (tx, rx) = mpsc::channel::<String>();
-    thread::spawn(move || loop {
-        let mut line = String::new();
-        match reader.read_line(&mut line) {
-            Ok(_) => {
-                if tx.send(line).is_err() {
-                    break;
-                }
-            }
-            _ => break,
+fn read_available_records(mut file: File) -> Result<Vec<String>, String> {
+    let mut records = Vec::new();
+    let mut buffer = vec![0; KMSG_RECORD_MAX];
+    loop {
+        match file.read(&mut buffer) {
+            Ok(0) => return Ok(records),
+            Ok(len) => records.push(String::from_utf8_lossy(&buffer[..len]).into_owned()),
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(records),
+            // The ring buffer wrapped around, the next read returns the oldest record left
+            Err(e) if e.raw_os_error() == Some(libc::EPIPE) => {}
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(format!("Could not read kernel messages: {e}")),
         }
-    });
-
-    rx
+    }
 }
 
 #[cfg(test)]
 mod test {
     use crate::libproc::proc_pid::am_root;
+    use std::time::Duration;
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    use super::BootClock;
+    #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+    use super::{
+        kmsg_write_records, KmsgEntry, KmsgReader, KmsgStart, SequenceTracker, KMSG_RECORD_MAX,
+        KMSG_WRITE_MAX,
+    };
+    use super::{kmsgbuf, parse_kmsg, parse_syslog, KmsgFilter, KmsgRecord, LogLevel};
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    use std::time::SystemTime;
+
+    const KMSG: &str = "\
+5,0,0,-;Linux version 6.1.0 (builder@host) #1 SMP
+6,339,5140900,-;NET: Registered protocol family 10
+12,1215,80345128,c;usb 1-1: new high-speed USB device\\x0anumber 2
+ SUBSYSTEM=usb
+ DEVICE=c189:1
+3,1216,80345200,+;continued
+";
+
+    #[test]
+    fn parse_records() {
+        let records = parse_kmsg(KMSG.as_bytes()).expect("Could not parse records");
+        assert_eq!(records.len(), 4);
+
+        assert_eq!(records[0].facility, 0);
+        assert_eq!(records[0].level, LogLevel::Notice);
+        assert_eq!(records[0].sequence, 0);
+        assert!(!records[0].continuation);
+        assert!(records[0].dict.is_empty());
+
+        assert_eq!(records[1].timestamp, Duration::from_micros(5_140_900));
+
+        assert_eq!(records[2].facility, 1);
+        assert_eq!(records[2].level, LogLevel::Warning);
+        assert!(records[2].continuation);
+        assert_eq!(
+            records[2].message,
+            "usb 1-1: new high-speed USB device\nnumber 2"
+        );
+        assert_eq!(
+            records[2].dict,
+            vec![
+                ("SUBSYSTEM".to_string(), "usb".to_string()),
+                ("DEVICE".to_string(), "c189:1".to_string())
+            ]
+        );
+
+        assert_eq!(records[3].level, LogLevel::Error);
+        assert!(records[3].continuation);
+    }
+
+    #[test]
+    fn parse_record_without_flag() {
+        let record: KmsgRecord = "6,1,2;no flag".parse().expect("Could not parse record");
+        assert_eq!(record.message, "no flag");
+        assert!(!record.continuation);
+    }
+
+    #[test]
+    fn parse_invalid_record() {
+        assert!(parse_kmsg("not a record\n".as_bytes()).is_err());
+        assert!("6,x,2,-;bad sequence".parse::<KmsgRecord>().is_err());
+    }
 
-    use super::kmsgbuf;
+    #[test]
+    fn parse_syslog_records() {
+        let dump = "\
+<6>[    0.000000] Linux version 6.1.0
+<3>[ 1234.500001] EXT4-fs error (device sda1): bad block
+<12>user message without timestamp
+";
+        let records = parse_syslog(dump.as_bytes()).expect("Could not parse records");
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].level, LogLevel::Info);
+        assert_eq!(records[0].timestamp, Duration::ZERO);
+        assert_eq!(records[0].message, "Linux version 6.1.0");
+        assert_eq!(records[1].level, LogLevel::Error);
+        assert_eq!(
+            records[1].timestamp,
+            Duration::from_secs(1234) + Duration::from_micros(500_001)
+        );
+        assert_eq!(records[1].sequence, 1);
+        assert_eq!(records[2].facility, 1);
+        assert_eq!(records[2].level, LogLevel::Warning);
+        assert_eq!(records[2].message, "user message without timestamp");
+    }
+
+    #[test]
+    fn parse_invalid_syslog() {
+        assert!(parse_syslog("no priority\n".as_bytes()).is_err());
+        assert!(parse_syslog("<6>[bad] timestamp\n".as_bytes()).is_err());
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn klog_records_test() {
+        if am_root() {
+            let records = super::klog_records().expect("klog_records() failed");
+            assert!(!records.is_empty());
+        } else {
+            println!("test skipped as it needs to be run as root");
+        }
+    }
+
+    #[test]
+    fn filter_records() {
+        let records = parse_kmsg(KMSG.as_bytes()).expect("Could not parse records");
+        assert_eq!(KmsgFilter::new().apply(records.clone()).len(), 4);
+
+        let errors = KmsgFilter::new()
+            .level(LogLevel::Error)
+            .apply(records.clone());
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].sequence, 1216);
+
+        let user = KmsgFilter::new().facility(1).apply(records.clone());
+        assert_eq!(user.len(), 1);
+        assert_eq!(user[0].sequence, 1215);
+
+        let window = KmsgFilter::new()
+            .since(Duration::from_secs(1))
+            .until(Duration::from_secs(80))
+            .apply(records);
+        assert_eq!(window.len(), 1);
+        assert_eq!(window[0].sequence, 339);
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn boot_clock_conversion() {
+        let boot_time = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let clock = BootClock::new(boot_time, Duration::from_secs(60));
+        let timestamp = Duration::from_secs(100);
+        let time = clock.to_system_time(timestamp);
+        assert_eq!(time, boot_time + Duration::from_secs(160));
+        assert_eq!(clock.to_timestamp(time), Some(timestamp));
+        assert_eq!(clock.to_timestamp(boot_time), None);
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn boot_clock_now() {
+        let clock = BootClock::now().expect("BootClock::now() failed");
+        assert!(clock.boot_time() < SystemTime::now());
+        let records = parse_kmsg(KMSG.as_bytes()).expect("Could not parse records");
+        let filter = KmsgFilter::new().until_time(&clock, clock.boot_time());
+        assert!(filter.apply(records.clone()).len() <= 1);
+        let filter = KmsgFilter::new().since_time(&clock, SystemTime::now());
+        assert!(filter.apply(records).is_empty());
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn filter_time_window() {
+        let boot_time = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let clock = BootClock::new(boot_time, Duration::ZERO);
+        let records = parse_kmsg(KMSG.as_bytes()).expect("Could not parse records");
+
+        let window = KmsgFilter::new()
+            .since_time(&clock, boot_time + Duration::from_secs(1))
+            .until_time(&clock, boot_time + Duration::from_secs(80))
+            .apply(records.clone());
+        assert_eq!(window.len(), 1);
+        assert_eq!(window[0].sequence, 339);
+
+        let before_boot = KmsgFilter::new()
+            .since_time(&clock, boot_time + Duration::from_secs(1))
+            .until_time(&clock, boot_time - Duration::from_secs(1));
+        assert!(before_boot.apply(records.clone()).is_empty());
+
+        // Setting the lower bound afterwards does not reopen the window
+        let before_boot = KmsgFilter::new()
+            .until_time(&clock, boot_time - Duration::from_secs(1))
+            .since_time(&clock, boot_time - Duration::from_secs(2));
+        assert!(before_boot.apply(records).is_empty());
+    }
+
+    #[test]
+    fn level_order() {
+        assert!(LogLevel::Emergency < LogLevel::Debug);
+        assert_eq!(LogLevel::from(3), LogLevel::Error);
+        assert_eq!(LogLevel::Warning.to_string(), "warn");
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+    #[test]
+    fn kmsg_records_test() {
+        if am_root() {
+            let records = super::kmsg_records().expect("kmsg_records() failed");
+            assert!(!records.is_empty());
+            // All the records in the buffer are read, without gaps
+            assert!(records
+                .windows(2)
+                .all(|pair| pair[1].sequence == pair[0].sequence + 1));
+        } else {
+            println!("test skipped as it needs to be run as root");
+        }
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+    #[test]
+    fn track_sequence_gaps() {
+        let mut tracker = SequenceTracker::default();
+        assert_eq!(tracker.track(10), 0);
+        assert_eq!(tracker.track(11), 0);
+        assert_eq!(tracker.track(15), 3);
+        assert_eq!(tracker.next, Some(16));
+
+        let mut tracker = SequenceTracker { next: Some(5) };
+        assert_eq!(tracker.track(8), 3);
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+    #[test]
+    fn kmsg_reader_from_sequence() {
+        if am_root() {
+            let mut reader = KmsgReader::open(KmsgStart::Beginning).expect("Could not open reader");
+            let first = match reader.try_next() {
+                Ok(Some(KmsgEntry::Record(record))) => record,
+                other => panic!("Unexpected first entry {:?}", other),
+            };
+            let second = match reader.try_next() {
+                Ok(Some(KmsgEntry::Record(record))) => record,
+                other => panic!("Unexpected second entry {:?}", other),
+            };
+
+            let mut reader = KmsgReader::open(KmsgStart::Sequence(second.sequence))
+                .expect("Could not open reader");
+            match reader.try_next() {
+                Ok(Some(KmsgEntry::Record(record))) => assert_eq!(record, second),
+                other => panic!("Unexpected entry {:?}", other),
+            }
+            assert_eq!(reader.next_sequence(), Some(second.sequence + 1));
+            assert!(first.sequence < second.sequence);
+        } else {
+            println!("test skipped as it needs to be run as root");
+        }
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+    #[test]
+    fn kmsg_reader_try_next_after_end() {
+        if am_root() {
+            let mut reader = KmsgReader::open(KmsgStart::Beginning).expect("Could not open reader");
+            while let Ok(Some(_)) = reader.try_next() {}
+            let next = reader.next_sequence().expect("No records read");
+
+            // Records before the start are skipped without waiting for the one to start at
+            let mut reader =
+                KmsgReader::open(KmsgStart::Sequence(next + 1000)).expect("Could not open reader");
+            let started = std::time::Instant::now();
+            assert_eq!(reader.try_next(), Ok(None));
+            assert!(started.elapsed() < Duration::from_secs(1));
+        } else {
+            println!("test skipped as it needs to be run as root");
+        }
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+    #[test]
+    fn kmsg_reader_follow() {
+        if am_root() {
+            let mut reader = KmsgReader::open(KmsgStart::End).expect("Could not open reader");
+            super::write(LogLevel::Debug, "libproc: kmsg_reader_follow test")
+                .expect("Could not write to /dev/kmsg");
+            // Other messages may be logged at the same time
+            let found = reader.by_ref().take(100).any(|entry| {
+                matches!(entry, Ok(KmsgEntry::Record(record))
+                    if record.message == "libproc: kmsg_reader_follow test")
+            });
+            assert!(found, "Message written was not read");
+        } else {
+            println!("test skipped as it needs to be run as root");
+        }
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+    #[test]
+    fn kmsg_reader_ends_after_error() {
+        // Reading a directory fails with EISDIR every time
+        let mut reader = KmsgReader {
+            file: std::fs::File::open(std::env::temp_dir()).expect("Could not open temp dir"),
+            sequence: SequenceTracker::default(),
+            skip_before: 0,
+            pending: None,
+            buffer: vec![0; KMSG_RECORD_MAX],
+            failed: false,
+        };
+        assert!(matches!(reader.next(), Some(Err(_))));
+        assert!(reader.next().is_none());
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+    #[test]
+    fn split_written_records() {
+        assert_eq!(
+            kmsg_write_records(LogLevel::Notice, "phase one\nphase two\n"),
+            vec!["<13>phase one\n", "<13>phase two\n"]
+        );
+        assert_eq!(kmsg_write_records(LogLevel::Emergency, ""), vec!["<8>\n"]);
+
+        // Multi-byte characters are not split across records
+        let long = "é".repeat(KMSG_WRITE_MAX);
+        let records = kmsg_write_records(LogLevel::Error, &long);
+        assert!(records.iter().all(|record| record.len() <= KMSG_WRITE_MAX));
+        let text: String = records
+            .iter()
+            .map(|record| {
+                record
+                    .strip_prefix("<11>")
+                    .and_then(|text| text.strip_suffix('\n'))
+                    .expect("Missing prefix or newline")
+            })
+            .collect();
+        assert_eq!(text, long);
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+    #[test]
+    fn write_test() {
+        if am_root() {
+            let mut reader = KmsgReader::open(KmsgStart::End).expect("Could not open reader");
+            super::write(LogLevel::Warning, "libproc: write test").expect("write() failed");
+            let record = reader
+                .by_ref()
+                .take(100)
+                .find_map(|entry| match entry {
+                    Ok(KmsgEntry::Record(record)) if record.message == "libproc: write test" => {
+                        Some(record)
+                    }
+                    _ => None,
+                })
+                .expect("Message written was not read");
+            assert_eq!(record.level, LogLevel::Warning);
+            assert_eq!(record.facility, 1);
+        } else {
+            println!("test skipped as it needs to be run as root");
+        }
+    }
 
     #[test]
     fn kmessage_buffer_test() {
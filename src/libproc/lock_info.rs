@@ -0,0 +1,382 @@
+use std::convert::TryFrom;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::os::unix::fs::MetadataExt;
+use std::path::PathBuf;
+
+use crate::libproc::file_info::list_fds;
+use crate::libproc::helpers::dev_major_minor;
+use crate::processes::{pids_by_type, ProcFilter};
+
+const PROC_LOCKS: &str = "/proc/locks";
+
+// Marker that precedes the description of a lock request blocked waiting for a lock
+const WAITER_MARKER: &str = "->";
+
+// Prefix of the lines of `/proc/<pid>/fdinfo/<fd>` describing the locks held through the fd
+const FDINFO_LOCK_PREFIX: &str = "lock:";
+
+/// The kind of a file lock
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LockKind {
+    /// Process-associated record lock taken with `fcntl(F_SETLK)` or `lockf()`
+    Posix,
+    /// Whole file lock taken with `flock()`
+    Flock,
+    /// Open file description record lock taken with `fcntl(F_OFD_SETLK)`
+    OpenFileDescription,
+    /// File lease taken with `fcntl(F_SETLEASE)`
+    Lease,
+    /// NFS delegation
+    Delegation,
+    /// Unknown
+    Unknown,
+}
+
+impl From<&str> for LockKind {
+    fn from(value: &str) -> LockKind {
+        match value {
+            "POSIX" => LockKind::Posix,
+            "FLOCK" => LockKind::Flock,
+            "OFDLCK" => LockKind::OpenFileDescription,
+            "LEASE" => LockKind::Lease,
+            "DELEG" => LockKind::Delegation,
+            _ => LockKind::Unknown,
+        }
+    }
+}
+
+/// The type of access a file lock protects
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LockAccess {
+    /// Shared (read) lock
+    Read,
+    /// Exclusive (write) lock
+    Write,
+    /// Unknown
+    Unknown,
+}
+
+impl From<&str> for LockAccess {
+    fn from(value: &str) -> LockAccess {
+        match value {
+            "READ" => LockAccess::Read,
+            "WRITE" => LockAccess::Write,
+            _ => LockAccess::Unknown,
+        }
+    }
+}
+
+/// A file lock, or a request blocked waiting for one, as listed in `/proc/locks`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileLock {
+    /// Ordinal number of the lock in `/proc/locks`. Waiters share the number of the lock they
+    /// are blocked on
+    pub id: u32,
+    /// Kind of lock
+    pub kind: LockKind,
+    /// The lock is mandatory rather than advisory
+    pub mandatory: bool,
+    /// Type of access the lock protects
+    pub access: LockAccess,
+    /// Pid of the process that owns the lock. The kernel does not report this for open file
+    /// description locks, which belong to an open file rather than a process, but
+    /// [`all_locks`] fills in a process that holds the lock through one of its file
+    /// descriptors when it can find one
+    pub pid: Option<u32>,
+    /// Major and minor device numbers of the filesystem containing the locked file
+    pub dev: (u32, u32),
+    /// Inode of the locked file
+    pub inode: u64,
+    /// First byte of the locked range
+    pub start: u64,
+    /// Last byte of the locked range, or `None` if the lock extends to the end of the file
+    pub end: Option<u64>,
+    /// Path of the locked file, if it could be resolved
+    pub path: Option<PathBuf>,
+    /// Lock requests blocked waiting for this lock
+    pub waiters: Vec<FileLock>,
+}
+
+impl FileLock {
+    /// Parse a line of `/proc/locks`, returning the lock and whether it is a blocked waiter
+    fn parse(line: &str) -> Option<(FileLock, bool)> {
+        let mut fields = line.split_ascii_whitespace().peekable();
+        let id = fields.next()?.strip_suffix(':')?.parse().ok()?;
+        let waiter = fields.next_if_eq(&WAITER_MARKER).is_some();
+        let kind = LockKind::from(fields.next()?);
+        let mandatory = match fields.next()? {
+            "MANDATORY" => true,
+            // Leases report their state (ACTIVE, BREAKING...) here
+            _ => false,
+        };
+        let access = LockAccess::from(fields.next()?);
+        let pid = fields.next()?.parse::<i64>().ok()?;
+        let mut location = fields.next()?.split(':');
+        let major = u32::from_str_radix(location.next()?, 16).ok()?;
+        let minor = u32::from_str_radix(location.next()?, 16).ok()?;
+        let inode = location.next()?.parse().ok()?;
+        let start = fields.next()?.parse().ok()?;
+        let end = match fields.next()? {
+            "EOF" => None,
+            end => Some(end.parse().ok()?),
+        };
+
+        Some((
+            FileLock {
+                id,
+                kind,
+                mandatory,
+                access,
+                pid: u32::try_from(pid).ok().filter(|pid| *pid > 0),
+                dev: (major, minor),
+                inode,
+                start,
+                end,
+                path: None,
+                waiters: Vec::new(),
+            },
+            waiter,
+        ))
+    }
+
+    // The two descriptions are of the same lock, ignoring the id and pid which differ between
+    // `/proc/locks` and `/proc/<pid>/fdinfo/<fd>`
+    fn same_lock(&self, other: &FileLock) -> bool {
+        self.kind == other.kind
+            && self.access == other.access
+            && self.dev == other.dev
+            && self.inode == other.inode
+            && self.start == other.start
+            && self.end == other.end
+    }
+}
+
+/// Parse the contents of `/proc/locks`, returning the locks held with any blocked waiters
+/// attached to the lock they are waiting for. Paths are not resolved.
+///
+/// # Errors
+///
+/// Will return `Err` if the contents cannot be read or a line cannot be parsed
+pub fn parse_locks<R: BufRead>(reader: R) -> Result<Vec<FileLock>, String> {
+    let mut locks: Vec<FileLock> = Vec::new();
+
+    for line in reader.lines() {
+        let line = line.map_err(|e| format!("Could not read locks: {e}"))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (lock, waiter) =
+            FileLock::parse(&line).ok_or_else(|| format!("Could not parse lock '{line}'"))?;
+        if waiter {
+            match locks.iter_mut().rev().find(|held| held.id == lock.id) {
+                Some(held) => held.waiters.push(lock),
+                None => locks.push(lock),
+            }
+        } else {
+            locks.push(lock);
+        }
+    }
+
+    Ok(locks)
+}
+
+// Find a path to a file with the given device and inode among the files open in process `pid`
+fn find_open_file(pid: u32, dev: (u32, u32), inode: u64) -> Option<PathBuf> {
+    let pid = i32::try_from(pid).ok()?;
+    list_fds(pid).ok()?.into_iter().find_map(|fd| {
+        let link = format!("/proc/{pid}/fd/{fd}");
+        let metadata = fs::metadata(&link).ok()?;
+        if metadata.ino() == inode && dev_major_minor(metadata.dev()) == dev {
+            fs::read_link(&link).ok()
+        } else {
+            None
+        }
+    })
+}
+
+// Find a file descriptor in process `pid` that `lock` is held through, from the `lock:` lines the
+// kernel lists in `/proc/<pid>/fdinfo/<fd>`, returning the path of the locked file
+fn find_lock_fd(pid: u32, lock: &FileLock) -> Option<PathBuf> {
+    let pid = i32::try_from(pid).ok()?;
+    list_fds(pid).ok()?.into_iter().find_map(|fd| {
+        let fdinfo = fs::read_to_string(format!("/proc/{pid}/fdinfo/{fd}")).ok()?;
+        let holds_lock = fdinfo
+            .lines()
+            .filter_map(|line| line.strip_prefix(FDINFO_LOCK_PREFIX))
+            .filter_map(|line| FileLock::parse(line.trim()))
+            .any(|(held, _)| held.same_lock(lock));
+        if holds_lock {
+            fs::read_link(format!("/proc/{pid}/fd/{fd}")).ok()
+        } else {
+            None
+        }
+    })
+}
+
+// Find a process holding the open file description lock `lock`, and the path of the locked file
+fn find_ofd_lock_holder(lock: &FileLock, all_pids: &[u32]) -> Option<(u32, PathBuf)> {
+    if lock.kind != LockKind::OpenFileDescription {
+        return None;
+    }
+    all_pids
+        .iter()
+        .find_map(|&pid| Some((pid, find_lock_fd(pid, lock)?)))
+}
+
+// Resolve the path of a lock by searching the files open in the owning process, or in all
+// processes if the kernel did not report its pid. The pid of an open file description lock is
+// only filled in if a process lists the lock as held through one of its file descriptors, rather
+// than taking any process that has the locked file open
+fn resolve(lock: &mut FileLock, all_pids: &[u32]) {
+    if let Some(pid) = lock.pid {
+        lock.path = find_open_file(pid, lock.dev, lock.inode);
+    } else if let Some((pid, path)) = find_ofd_lock_holder(lock, all_pids) {
+        lock.pid = Some(pid);
+        lock.path = Some(path);
+    } else {
+        lock.path = all_pids
+            .iter()
+            .find_map(|&pid| find_open_file(pid, lock.dev, lock.inode));
+    }
+
+    for waiter in &mut lock.waiters {
+        resolve(waiter, all_pids);
+    }
+}
+
+/// Get all the file locks on the system, from `/proc/locks`, with blocked lock requests attached
+/// to the lock they are waiting for.
+///
+/// The path of each locked file is resolved by searching the files open in processes, and the
+/// owning pid of open file description locks by searching the locks processes list in
+/// `/proc/<pid>/fdinfo/<fd>`. This only succeeds for processes whose file descriptors the caller
+/// has permission to read.
+///
+/// # Errors
+///
+/// Will return `Err` if `/proc/locks` cannot be read or parsed
+///
+/// # Examples
+///
+/// ```
+/// use libproc::lock_info::all_locks;
+///
+/// for lock in all_locks().expect("Could not read file locks") {
+///     println!("{:?} {:?} lock held by {:?} on {:?}, {} waiters", lock.kind, lock.access,
+///              lock.pid, lock.path, lock.waiters.len());
+/// }
+/// ```
+pub fn all_locks() -> Result<Vec<FileLock>, String> {
+    let file = File::open(PROC_LOCKS)
+        .map_err(|e| format!("Could not open /proc file '{PROC_LOCKS}': {e}"))?;
+    let mut locks = parse_locks(BufReader::new(file))?;
+
+    // Only scan all processes if there is a lock without a pid to resolve
+    let needs_all_pids = locks
+        .iter()
+        .flat_map(|lock| std::iter::once(lock).chain(&lock.waiters))
+        .any(|lock| lock.pid.is_none());
+    let all_pids = if needs_all_pids {
+        pids_by_type(ProcFilter::All).map_err(|e| e.to_string())?
+    } else {
+        Vec::new()
+    };
+
+    for lock in &mut locks {
+        resolve(lock, &all_pids);
+    }
+
+    Ok(locks)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{all_locks, parse_locks, LockAccess, LockKind};
+    use std::fs::OpenOptions;
+    use std::os::unix::fs::MetadataExt;
+    use std::os::unix::io::AsRawFd;
+    use std::process;
+
+    const PROC_LOCKS: &str = "\
+1: POSIX  ADVISORY  WRITE 1234 08:01:1234567 0 EOF
+1: -> POSIX  ADVISORY  WRITE 4321 08:01:1234567 0 EOF
+2: FLOCK  ADVISORY  READ  5678 00:1f:12 0 EOF
+3: OFDLCK ADVISORY  READ  -1 fd:00:987 100 199
+4: LEASE  ACTIVE    READ  42 00:2a:55 0 EOF
+5: POSIX  MANDATORY WRITE 99 08:01:77 0 0
+";
+
+    #[test]
+    fn parse_all_kinds() {
+        let locks = parse_locks(PROC_LOCKS.as_bytes()).expect("Could not parse locks");
+        assert_eq!(locks.len(), 5);
+
+        assert_eq!(locks[0].kind, LockKind::Posix);
+        assert_eq!(locks[0].access, LockAccess::Write);
+        assert_eq!(locks[0].pid, Some(1234));
+        assert_eq!(locks[0].dev, (8, 1));
+        assert_eq!(locks[0].inode, 1_234_567);
+        assert_eq!(locks[0].end, None);
+        assert!(!locks[0].mandatory);
+
+        assert_eq!(locks[1].kind, LockKind::Flock);
+        assert_eq!(locks[1].access, LockAccess::Read);
+        assert_eq!(locks[1].dev, (0, 0x1f));
+
+        assert_eq!(locks[2].kind, LockKind::OpenFileDescription);
+        assert_eq!(locks[2].pid, None);
+        assert_eq!(locks[2].dev, (0xfd, 0));
+        assert_eq!(locks[2].start, 100);
+        assert_eq!(locks[2].end, Some(199));
+
+        assert_eq!(locks[3].kind, LockKind::Lease);
+        assert!(locks[4].mandatory);
+    }
+
+    #[test]
+    fn parse_waiters() {
+        let locks = parse_locks(PROC_LOCKS.as_bytes()).expect("Could not parse locks");
+        assert_eq!(locks[0].waiters.len(), 1);
+        assert_eq!(locks[0].waiters[0].pid, Some(4321));
+        assert!(locks[1].waiters.is_empty());
+    }
+
+    #[test]
+    fn parse_invalid_lock() {
+        assert!(parse_locks("1: POSIX garbage".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn ofd_lock_holder() {
+        let dir = tempfile::tempdir().expect("Could not create temp dir");
+        let path = dir.path().join("locked");
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)
+            .expect("Could not create file");
+        let mut lock: libc::flock = unsafe { std::mem::zeroed() };
+        // Lock types and whence values are small constants
+        #[allow(clippy::cast_possible_truncation)]
+        {
+            lock.l_type = libc::F_WRLCK as libc::c_short;
+            lock.l_whence = libc::SEEK_SET as libc::c_short;
+        }
+        lock.l_len = 10;
+        let ret = unsafe { libc::fcntl(file.as_raw_fd(), libc::F_OFD_SETLK, &lock) };
+        assert_eq!(ret, 0, "Could not take an OFD lock");
+
+        let inode = file.metadata().expect("Could not get metadata").ino();
+        let locks = all_locks().expect("Could not read file locks");
+        let held = locks
+            .iter()
+            .find(|lock| lock.inode == inode && lock.kind == LockKind::OpenFileDescription)
+            .expect("Could not find the OFD lock");
+        assert_eq!(held.pid, Some(process::id()));
+        assert_eq!(held.path.as_deref(), Some(path.as_path()));
+        assert_eq!(held.end, Some(9));
+    }
+}
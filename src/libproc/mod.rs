@@ -2,13 +2,31 @@
 /// BSD specific information - very macos specific
 pub mod bsd_info;
 
-#[cfg(any(target_os = "macos", doc))]
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// Information about the cgroups of processes and their resource usage
+pub mod cgroup;
+
 /// Information about Files and File Descriptors used by processes
 pub mod file_info;
 
 /// Get messages from the kernel message buffer
 pub mod kmesg_buffer;
 
+/// Recognise events such as OOM kills and segfaults in kernel messages
+pub mod kernel_events;
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// Information about file locks held by processes
+pub mod lock_info;
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// Information about the mounts visible to processes
+pub mod mount_info;
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// Information about the pages of a process's virtual memory
+pub mod page_info;
+
 /// Information about Process Resource Usage - added in Mac OS X 10.9
 pub mod pid_rusage;
 
@@ -23,13 +41,18 @@ pub mod task_info;
 /// Information about Threads running inside processes
 pub mod thread_info;
 
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// Estimation of the working set size of processes
+pub mod working_set;
+
 #[cfg(any(target_os = "macos", doc))]
 /// Information about Work Queues - very macos specific
 pub mod work_queue_info;
 
-#[cfg(any(target_os = "macos", doc))]
 /// Information about Network usage by a process
 pub mod net_info;
 
 mod helpers;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod sock_diag;
 pub(crate) mod sys;
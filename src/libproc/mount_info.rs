@@ -0,0 +1,208 @@
+use std::ffi::OsString;
+use std::io::BufRead;
+use std::os::unix::ffi::OsStringExt;
+use std::path::{Path, PathBuf};
+use std::str::{self, FromStr};
+
+// Separator between the optional fields and the filesystem type in a mountinfo line
+const OPTIONAL_FIELDS_END: &str = "-";
+
+/// A mount visible in a process's mount namespace, from `/proc/<pid>/mountinfo`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MountInfo {
+    /// Unique id of the mount
+    pub mount_id: u32,
+    /// Id of the parent mount, or of this mount for the root of the mount tree
+    pub parent_id: u32,
+    /// Major and minor device numbers of the mounted filesystem
+    pub dev: (u32, u32),
+    /// Directory within the filesystem that forms the root of this mount
+    pub root: PathBuf,
+    /// Mount point, relative to the process's root directory
+    pub mount_point: PathBuf,
+    /// Per-mount options, e.g. "rw", "noatime"
+    pub mount_options: Vec<String>,
+    /// Optional fields describing propagation, e.g. "shared:1", "master:2"
+    pub optional_fields: Vec<String>,
+    /// Filesystem type, e.g. "ext4", "overlay"
+    pub fs_type: String,
+    /// Filesystem specific source, e.g. "/dev/sda1", or `None` if "none"
+    pub source: Option<String>,
+    /// Per-superblock options
+    pub super_options: Vec<String>,
+}
+
+// Paths in mountinfo have space, tab, newline and backslash escaped as octal, e.g. "\040".
+// Other bytes are as in the path, which need not be valid UTF-8
+fn unescape(field: &[u8]) -> PathBuf {
+    let mut unescaped = Vec::with_capacity(field.len());
+    let mut i = 0;
+    while i < field.len() {
+        if field[i] == b'\\' {
+            if let Some(byte) = field
+                .get(i + 1..i + 4)
+                .and_then(|octal| str::from_utf8(octal).ok())
+                .and_then(|octal| u8::from_str_radix(octal, 8).ok())
+            {
+                unescaped.push(byte);
+                i += 4;
+                continue;
+            }
+        }
+        unescaped.push(field[i]);
+        i += 1;
+    }
+    PathBuf::from(OsString::from_vec(unescaped))
+}
+
+fn text(field: &[u8]) -> String {
+    String::from_utf8_lossy(field).into_owned()
+}
+
+fn options(field: &[u8]) -> Vec<String> {
+    field.split(|&b| b == b',').map(text).collect()
+}
+
+impl MountInfo {
+    // Parse a single line of a `/proc/<pid>/mountinfo` file, which may contain paths that are
+    // not valid UTF-8
+    fn parse(line: &[u8]) -> Result<Self, String> {
+        let error = || format!("Could not parse mountinfo line '{}'", text(line));
+        let number = |field: &[u8]| -> Result<u32, String> {
+            str::from_utf8(field)
+                .ok()
+                .and_then(|f| f.parse().ok())
+                .ok_or_else(error)
+        };
+        let mut fields = line
+            .split(u8::is_ascii_whitespace)
+            .filter(|field| !field.is_empty());
+
+        let mount_id = number(fields.next().ok_or_else(error)?)?;
+        let parent_id = number(fields.next().ok_or_else(error)?)?;
+        let dev_field = fields.next().ok_or_else(error)?;
+        let separator = dev_field
+            .iter()
+            .position(|&b| b == b':')
+            .ok_or_else(error)?;
+        let dev = (
+            number(&dev_field[..separator])?,
+            number(&dev_field[separator + 1..])?,
+        );
+        let root = fields.next().map(unescape).ok_or_else(error)?;
+        let mount_point = fields.next().map(unescape).ok_or_else(error)?;
+        let mount_options = fields.next().map(options).ok_or_else(error)?;
+        let optional_fields = fields
+            .by_ref()
+            .take_while(|f| *f != OPTIONAL_FIELDS_END.as_bytes())
+            .map(text)
+            .collect();
+        let fs_type = fields.next().map(text).ok_or_else(error)?;
+        let source = fields.next().map(unescape).ok_or_else(error)?;
+        let super_options = fields.next().map(options).unwrap_or_default();
+
+        Ok(MountInfo {
+            mount_id,
+            parent_id,
+            dev,
+            root,
+            mount_point,
+            mount_options,
+            optional_fields,
+            fs_type,
+            source: if source == Path::new("none") {
+                None
+            } else {
+                Some(source.to_string_lossy().into_owned())
+            },
+            super_options,
+        })
+    }
+}
+
+impl FromStr for MountInfo {
+    type Err = String;
+
+    /// Parse a single line of a `/proc/<pid>/mountinfo` file
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        MountInfo::parse(line.as_bytes())
+    }
+}
+
+/// Parse the contents of a `/proc/<pid>/mountinfo` file
+///
+/// # Errors
+///
+/// Will return `Err` if the contents cannot be read or a line cannot be parsed
+pub fn parse_mountinfo<R: BufRead>(reader: R) -> Result<Vec<MountInfo>, String> {
+    let mut mounts = Vec::new();
+    // Read lines as bytes, as paths need not be valid UTF-8
+    for line in reader.split(b'\n') {
+        let line = line.map_err(|e| format!("Could not read mountinfo: {e}"))?;
+        if !line.trim_ascii().is_empty() {
+            mounts.push(MountInfo::parse(&line)?);
+        }
+    }
+    Ok(mounts)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_mountinfo, MountInfo};
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+
+    const MOUNTINFO: &str = "\
+22 1 253:0 / / rw,relatime shared:1 - ext4 /dev/mapper/root rw,errors=remount-ro
+36 22 0:32 / /sys/fs/cgroup ro,nosuid,nodev,noexec shared:9 master:2 - tmpfs tmpfs ro,mode=755
+61 22 0:45 /data /mnt/my\\040data rw,relatime - overlay none rw,lowerdir=/l,upperdir=/u
+";
+
+    #[test]
+    fn parse_root_mount() {
+        let mounts = parse_mountinfo(MOUNTINFO.as_bytes()).expect("Could not parse mountinfo");
+        assert_eq!(mounts.len(), 3);
+        let root = &mounts[0];
+        assert_eq!(root.mount_id, 22);
+        assert_eq!(root.parent_id, 1);
+        assert_eq!(root.dev, (253, 0));
+        assert_eq!(root.root, Path::new("/"));
+        assert_eq!(root.mount_point, Path::new("/"));
+        assert_eq!(root.mount_options, vec!["rw", "relatime"]);
+        assert_eq!(root.optional_fields, vec!["shared:1"]);
+        assert_eq!(root.fs_type, "ext4");
+        assert_eq!(root.source.as_deref(), Some("/dev/mapper/root"));
+        assert_eq!(root.super_options, vec!["rw", "errors=remount-ro"]);
+    }
+
+    #[test]
+    fn parse_multiple_optional_fields() {
+        let mounts = parse_mountinfo(MOUNTINFO.as_bytes()).expect("Could not parse mountinfo");
+        assert_eq!(mounts[1].optional_fields, vec!["shared:9", "master:2"]);
+        assert_eq!(mounts[1].fs_type, "tmpfs");
+    }
+
+    #[test]
+    fn parse_escaped_path() {
+        let mounts = parse_mountinfo(MOUNTINFO.as_bytes()).expect("Could not parse mountinfo");
+        assert_eq!(mounts[2].root, Path::new("/data"));
+        assert_eq!(mounts[2].mount_point, Path::new("/mnt/my data"));
+        assert!(mounts[2].optional_fields.is_empty());
+        assert_eq!(mounts[2].source, None);
+    }
+
+    #[test]
+    fn parse_non_utf8_path() {
+        let line = b"62 22 0:46 / /mnt/caf\xe9\\040bar rw - tmpfs tmpfs rw\n";
+        let mounts = parse_mountinfo(&line[..]).expect("Could not parse mountinfo");
+        assert_eq!(
+            mounts[0].mount_point.as_os_str().as_bytes(),
+            b"/mnt/caf\xe9 bar"
+        );
+    }
+
+    #[test]
+    fn parse_invalid_line() {
+        assert!("22 1 253:0 / /".parse::<MountInfo>().is_err());
+    }
+}
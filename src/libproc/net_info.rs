@@ -1,14 +1,34 @@
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+use crate::libproc::file_info::list_fds;
+#[cfg(target_os = "macos")]
 use crate::libproc::file_info::{PIDFDInfo, PIDFDInfoFlavor};
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+use crate::libproc::proc_pid::{self, Namespace};
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use crate::libproc::sock_diag;
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+use crate::processes::pids_by_namespace;
+use crate::processes::{pids_by_type, ProcFilter};
 
+use libc::c_int;
 #[cfg(target_os = "macos")]
-use libc::SOCK_MAXADDRLEN;
 use libc::{
-    c_char, c_int, c_short, c_uchar, c_ushort, gid_t, in6_addr, in_addr, off_t, sockaddr_un, uid_t,
-    IF_NAMESIZE,
+    c_char, c_short, c_uchar, c_ushort, gid_t, in6_addr, in_addr, off_t, sockaddr_un, uid_t,
+    IF_NAMESIZE, SOCK_MAXADDRLEN,
 };
-#[cfg(target_os = "linux")]
-pub const SOCK_MAXADDRLEN: c_int = 255;
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+use std::fs::{self, File};
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+use std::io::{BufRead, BufReader};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+use std::net::{SocketAddrV4, SocketAddrV6};
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+use std::path::Path;
 
+#[cfg(target_os = "macos")]
 /// Socket File Descriptor Info
 #[repr(C)]
 #[derive(Default)]
@@ -19,6 +39,7 @@ pub struct SocketFDInfo {
     pub psi: SocketInfo,
 }
 
+#[cfg(target_os = "macos")]
 /// Proc File Info
 #[repr(C)]
 #[derive(Default)]
@@ -35,12 +56,14 @@ pub struct ProcFileInfo {
     pub rfu_1: i32,
 }
 
+#[cfg(target_os = "macos")]
 impl PIDFDInfo for SocketFDInfo {
     fn flavor() -> PIDFDInfoFlavor {
         PIDFDInfoFlavor::SocketInfo
     }
 }
 
+#[cfg(target_os = "macos")]
 /// Socket Info Kind
 #[derive(Copy, Clone, Debug)]
 pub enum SocketInfoKind {
@@ -62,6 +85,7 @@ pub enum SocketInfoKind {
     Unknown,
 }
 
+#[cfg(target_os = "macos")]
 impl From<c_int> for SocketInfoKind {
     fn from(value: c_int) -> SocketInfoKind {
         match value {
@@ -77,6 +101,7 @@ impl From<c_int> for SocketInfoKind {
     }
 }
 
+#[cfg(target_os = "macos")]
 /// Socket Info
 #[repr(C)]
 #[derive(Default)]
@@ -123,6 +148,7 @@ pub struct SocketInfo {
     pub soi_proto: SocketInfoProto,
 }
 
+#[cfg(target_os = "macos")]
 /// Struct for V Info Stat
 #[repr(C)]
 #[derive(Default)]
@@ -171,6 +197,7 @@ pub struct VInfoStat {
     pub vst_qspare: [i64; 2],
 }
 
+#[cfg(target_os = "macos")]
 /// Socket Buffer Info
 #[repr(C)]
 #[derive(Default)]
@@ -191,6 +218,7 @@ pub struct SockBufInfo {
     pub sbi_timeo: c_short,
 }
 
+#[cfg(target_os = "macos")]
 /// Socket Info Proto
 #[repr(C)]
 pub union SocketInfoProto {
@@ -208,6 +236,7 @@ pub union SocketInfoProto {
     pub pri_kern_ctl: KernCtlInfo,
 }
 
+#[cfg(target_os = "macos")]
 impl Default for SocketInfoProto {
     fn default() -> SocketInfoProto {
         SocketInfoProto {
@@ -216,6 +245,7 @@ impl Default for SocketInfoProto {
     }
 }
 
+#[cfg(target_os = "macos")]
 /// struct for holding IP4 or IP6 addresses
 #[repr(C)]
 #[derive(Copy, Clone)]
@@ -226,6 +256,7 @@ pub struct In4In6Addr {
     pub i46a_addr4: in_addr,
 }
 
+#[cfg(target_os = "macos")]
 impl Default for In4In6Addr {
     fn default() -> In4In6Addr {
         In4In6Addr {
@@ -235,6 +266,7 @@ impl Default for In4In6Addr {
     }
 }
 
+#[cfg(target_os = "macos")]
 /// `InSocketInfo` struct
 #[repr(C)]
 #[derive(Copy, Clone, Default)]
@@ -265,6 +297,7 @@ pub struct InSockInfo {
     pub insi_v6: InSIV6,
 }
 
+#[cfg(target_os = "macos")]
 /// In Socket Info `InSIV4` struct
 #[repr(C)]
 #[derive(Copy, Clone, Default)]
@@ -273,6 +306,7 @@ pub struct InSIV4 {
     pub in4_top: c_uchar, // NOTE: Should be in4_tos!
 }
 
+#[cfg(target_os = "macos")]
 /// In Socket Info `InSIV6` struct
 #[repr(C)]
 #[derive(Copy, Clone, Default)]
@@ -287,6 +321,7 @@ pub struct InSIV6 {
     pub in6_hops: c_short,
 }
 
+#[cfg(target_os = "macos")]
 /// In Socket Info `InSIAddr` union for v4 and v6 addresses
 #[repr(C)]
 #[derive(Copy, Clone)]
@@ -297,6 +332,7 @@ pub union InSIAddr {
     pub ina_6: in6_addr,
 }
 
+#[cfg(target_os = "macos")]
 impl Default for InSIAddr {
     fn default() -> InSIAddr {
         InSIAddr {
@@ -306,7 +342,7 @@ impl Default for InSIAddr {
 }
 
 /// TCP SI State struct
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum TcpSIState {
     /// Closed
     Closed = 0,
@@ -356,8 +392,32 @@ impl From<c_int> for TcpSIState {
     }
 }
 
+impl TcpSIState {
+    /// Convert a TCP state as numbered by the Linux kernel (e.g. in `/proc/net/tcp`), which
+    /// differs from the BSD numbering used by `From<c_int>`
+    #[must_use]
+    pub fn from_linux(state: u8) -> TcpSIState {
+        match state {
+            1 => TcpSIState::Established,
+            2 => TcpSIState::SynSent,
+            3 | 12 => TcpSIState::SynReceived,
+            4 => TcpSIState::FinWait1,
+            5 => TcpSIState::FinWait2,
+            6 => TcpSIState::TimeWait,
+            7 => TcpSIState::Closed,
+            8 => TcpSIState::CloseWait,
+            9 => TcpSIState::LastAck,
+            10 => TcpSIState::Listen,
+            11 => TcpSIState::Closing,
+            _ => TcpSIState::Unknown,
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
 const TSI_T_NTIMERS: usize = 4;
 
+#[cfg(target_os = "macos")]
 /// TCP Socket Info struct
 #[repr(C)]
 #[derive(Copy, Clone, Default)]
@@ -378,6 +438,7 @@ pub struct TcpSockInfo {
     pub tcpsi_tp: u64,
 }
 
+#[cfg(target_os = "macos")]
 /// Unix Domain Socket Info `UnSockInfo` struct
 #[repr(C)]
 #[derive(Copy, Clone, Default)]
@@ -392,6 +453,7 @@ pub struct UnSockInfo {
     pub unsi_caddr: UnSIAddr,
 }
 
+#[cfg(target_os = "macos")]
 /// Unix Socket Info Address `UnSIAddr` union
 #[repr(C)]
 #[derive(Copy, Clone)]
@@ -402,6 +464,7 @@ pub union UnSIAddr {
     pub ua_dummy: [c_char; SOCK_MAXADDRLEN as usize],
 }
 
+#[cfg(target_os = "macos")]
 impl Default for UnSIAddr {
     fn default() -> UnSIAddr {
         UnSIAddr {
@@ -410,6 +473,7 @@ impl Default for UnSIAddr {
     }
 }
 
+#[cfg(target_os = "macos")]
 /// `NDrvInfo` struct for `PF_NDRV Sockets`
 #[repr(C)]
 #[derive(Copy, Clone, Default)]
@@ -422,6 +486,7 @@ pub struct NdrvInfo {
     pub ndrvsi_if_name: [c_char; IF_NAMESIZE],
 }
 
+#[cfg(target_os = "macos")]
 /// Kernel Event Info struct
 #[repr(C)]
 #[derive(Copy, Clone, Default)]
@@ -434,8 +499,10 @@ pub struct KernEventInfo {
     pub kesi_subclass_filter: u32,
 }
 
+#[cfg(target_os = "macos")]
 const MAX_KCTL_NAME: usize = 96;
 
+#[cfg(target_os = "macos")]
 /// Kernel Control Info struct
 #[repr(C)]
 #[derive(Copy, Clone)]
@@ -456,6 +523,7 @@ pub struct KernCtlInfo {
     pub kcsi_name: [c_char; MAX_KCTL_NAME],
 }
 
+#[cfg(target_os = "macos")]
 impl Default for KernCtlInfo {
     fn default() -> KernCtlInfo {
         KernCtlInfo {
@@ -469,3 +537,1578 @@ impl Default for KernCtlInfo {
         }
     }
 }
+
+/// Information about an IPv4 or IPv6 socket
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InetSocket {
+    /// Local address and port. For raw sockets the port is the IP protocol number
+    pub local: SocketAddr,
+    /// Remote address and port, unspecified (e.g. `0.0.0.0:0`) if not connected
+    pub remote: SocketAddr,
+    /// TCP state. Connectionless sockets are reported as `Closed`, or `Established` if connected
+    pub state: TcpSIState,
+    /// Bytes in the send queue
+    pub tx_queue: u32,
+    /// Bytes in the receive queue
+    pub rx_queue: u32,
+    /// Effective user id of the creator of the socket
+    pub uid: u32,
+    /// Inode of the socket (on macOS the kernel's opaque socket handle)
+    pub inode: u64,
+}
+
+/// The type of a Unix domain socket
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum UnixSocketType {
+    /// `SOCK_STREAM`
+    Stream,
+    /// `SOCK_DGRAM`
+    Datagram,
+    /// `SOCK_SEQPACKET`
+    SeqPacket,
+    /// Unknown
+    Unknown,
+}
+
+impl From<c_int> for UnixSocketType {
+    fn from(value: c_int) -> UnixSocketType {
+        match value {
+            libc::SOCK_STREAM => UnixSocketType::Stream,
+            libc::SOCK_DGRAM => UnixSocketType::Datagram,
+            libc::SOCK_SEQPACKET => UnixSocketType::SeqPacket,
+            _ => UnixSocketType::Unknown,
+        }
+    }
+}
+
+/// Information about a Unix domain socket
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnixSocket {
+    /// Type of the socket
+    pub socket_type: UnixSocketType,
+    /// The socket is listening for connections
+    pub listening: bool,
+    /// The socket is connected to a peer
+    pub connected: bool,
+    /// Path the socket is bound to, if any. Names in the Linux abstract namespace start with '@'
+    pub path: Option<String>,
+    /// Inode of the socket (on macOS the kernel's opaque socket handle)
+    pub inode: u64,
+    /// Inode of the peer socket, if reported alongside the socket. This is only the case on
+    /// macOS, use [`unix_connections`] to resolve peers on all platforms
+    pub peer: Option<u64>,
+}
+
+/// Information about a Linux netlink socket
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetlinkSocket {
+    /// Netlink protocol family, e.g. `NETLINK_ROUTE`
+    pub protocol: u32,
+    /// Port id of the socket, usually the pid of the process that opened it
+    pub port_id: u32,
+    /// Bitmask of the multicast groups the socket is subscribed to
+    pub groups: u32,
+    /// Bytes allocated to the send queue
+    pub tx_queue: u32,
+    /// Bytes allocated to the receive queue
+    pub rx_queue: u32,
+    /// Inode of the socket
+    pub inode: u64,
+}
+
+/// A socket, with safe access to its addresses and state
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Socket {
+    /// TCP socket
+    Tcp(InetSocket),
+    /// UDP socket
+    Udp(InetSocket),
+    /// Unix domain socket
+    Unix(UnixSocket),
+    /// Raw IP socket
+    Raw(InetSocket),
+    /// Netlink socket (Linux only)
+    Netlink(NetlinkSocket),
+}
+
+impl Socket {
+    /// The inode of the socket (on macOS the kernel's opaque socket handle)
+    #[must_use]
+    pub fn inode(&self) -> u64 {
+        match self {
+            Socket::Tcp(inet) | Socket::Udp(inet) | Socket::Raw(inet) => inet.inode,
+            Socket::Unix(unix) => unix.inode,
+            Socket::Netlink(netlink) => netlink.inode,
+        }
+    }
+
+    /// The IP socket information, if this is a TCP, UDP or raw socket
+    #[must_use]
+    pub fn inet(&self) -> Option<&InetSocket> {
+        match self {
+            Socket::Tcp(inet) | Socket::Udp(inet) | Socket::Raw(inet) => Some(inet),
+            _ => None,
+        }
+    }
+
+    /// The local address of an IP socket
+    #[must_use]
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.inet().map(|inet| inet.local)
+    }
+
+    /// The remote address of an IP socket
+    #[must_use]
+    pub fn remote_addr(&self) -> Option<SocketAddr> {
+        self.inet().map(|inet| inet.remote)
+    }
+
+    /// The state of a TCP socket
+    #[must_use]
+    pub fn state(&self) -> Option<TcpSIState> {
+        match self {
+            Socket::Tcp(inet) => Some(inet.state),
+            _ => None,
+        }
+    }
+}
+
+/// A socket open in a process, and the file descriptor it is open on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenSocket {
+    /// File descriptor number
+    pub fd: i32,
+    /// The socket
+    pub socket: Socket,
+}
+
+/// The protocol of a [`Socket`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SocketProtocol {
+    /// TCP
+    Tcp,
+    /// UDP
+    Udp,
+    /// Unix domain
+    Unix,
+    /// Raw IP
+    Raw,
+    /// Netlink
+    Netlink,
+}
+
+impl Socket {
+    /// The protocol of the socket
+    #[must_use]
+    pub fn protocol(&self) -> SocketProtocol {
+        match self {
+            Socket::Tcp(_) => SocketProtocol::Tcp,
+            Socket::Udp(_) => SocketProtocol::Udp,
+            Socket::Unix(_) => SocketProtocol::Unix,
+            Socket::Raw(_) => SocketProtocol::Raw,
+            Socket::Netlink(_) => SocketProtocol::Netlink,
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// TCP metrics reported by the kernel in `struct tcp_info`. Times are in microseconds
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TcpInfo {
+    /// TCP state
+    pub state: TcpSIState,
+    /// Number of unrecovered retransmission timeouts
+    pub retransmits: u8,
+    /// Retransmission timeout
+    pub rto: u32,
+    /// Maximum segment size for sending
+    pub snd_mss: u32,
+    /// Number of segments sent but not acknowledged
+    pub unacked: u32,
+    /// Number of segments considered lost
+    pub lost: u32,
+    /// Smoothed round trip time
+    pub rtt: u32,
+    /// Round trip time variation
+    pub rttvar: u32,
+    /// Slow start threshold, in segments
+    pub snd_ssthresh: u32,
+    /// Congestion window, in segments
+    pub snd_cwnd: u32,
+    /// Total number of segments retransmitted
+    pub total_retrans: u32,
+    /// Bytes sent and acknowledged by the peer (since Linux 4.1)
+    pub bytes_acked: Option<u64>,
+    /// Bytes received (since Linux 4.1)
+    pub bytes_received: Option<u64>,
+    /// Minimum round trip time seen (since Linux 4.6)
+    pub min_rtt: Option<u32>,
+    /// Most recent delivery rate in bytes per second (since Linux 4.9)
+    pub delivery_rate: Option<u64>,
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// An IP socket and, for TCP sockets when available, its TCP metrics
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InetSocketInfo {
+    /// The socket
+    pub socket: InetSocket,
+    /// TCP metrics, only available from the `INET_DIAG` netlink backend
+    pub tcp_info: Option<TcpInfo>,
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// Get the TCP or UDP sockets in the caller's network namespace.
+///
+/// Sockets are enumerated with the `INET_DIAG` netlink interface, which is much faster than
+/// parsing `/proc/net` on hosts with many connections and reports [`TcpInfo`] metrics such as
+/// round trip time and retransmits. If netlink is not available the socket tables in
+/// `/proc/net` are parsed instead, without metrics.
+///
+/// # Errors
+///
+/// Will return `Err` if `protocol` is not [`SocketProtocol::Tcp`] or [`SocketProtocol::Udp`],
+/// or the sockets cannot be read with either method
+///
+/// # Examples
+///
+/// ```
+/// use libproc::net_info::{inet_sockets, SocketProtocol};
+///
+/// for info in inet_sockets(SocketProtocol::Tcp).expect("Could not list TCP sockets") {
+///     if let Some(tcp_info) = info.tcp_info {
+///         println!("{} -> {} rtt {}us cwnd {}", info.socket.local, info.socket.remote,
+///                  tcp_info.rtt, tcp_info.snd_cwnd);
+///     }
+/// }
+/// ```
+pub fn inet_sockets(protocol: SocketProtocol) -> Result<Vec<InetSocketInfo>, String> {
+    let (ip_protocol, tables) = match protocol {
+        SocketProtocol::Tcp => (IPPROTO_TCP, &PROC_NET_TCP),
+        SocketProtocol::Udp => (IPPROTO_UDP, &PROC_NET_UDP),
+        _ => return Err(format!("{protocol:?} sockets are not IP sockets")),
+    };
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    if let Ok(sockets) = sock_diag::inet_sockets(ip_protocol) {
+        return Ok(sockets);
+    }
+    #[cfg(target_os = "redox")]
+    let _ = ip_protocol;
+
+    let mut sockets = Vec::new();
+    for name in tables {
+        if let Some(reader) = open_socket_table(Path::new(PROC_NET), name) {
+            sockets.extend(
+                parse_proc_net_inet(reader)?
+                    .into_iter()
+                    .map(|socket| InetSocketInfo {
+                        socket,
+                        tcp_info: None,
+                    }),
+            );
+        }
+    }
+    Ok(sockets)
+}
+
+/// A process with a file descriptor referencing a socket
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SocketOwner {
+    /// Pid of the process
+    pub pid: u32,
+    /// File descriptor the socket is open on in the process
+    pub fd: i32,
+    /// Command name of the process
+    pub command: String,
+}
+
+/// A socket on the system and the processes that reference it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemSocket {
+    /// The socket
+    pub socket: Socket,
+    /// The processes and file descriptors referencing the socket. A socket inherited across
+    /// `fork()` or passed between processes has more than one owner
+    pub owners: Vec<SocketOwner>,
+    /// Inode of the network namespace the socket belongs to (Linux only)
+    pub net_namespace: Option<u64>,
+}
+
+impl SystemSocket {
+    /// Return true if no process the caller can inspect references the socket
+    #[must_use]
+    pub fn is_unowned(&self) -> bool {
+        self.owners.is_empty()
+    }
+}
+
+/// A filter selecting which sockets [`all_sockets`] returns. An empty filter selects all sockets,
+/// and each condition added narrows the selection.
+///
+/// # Examples
+///
+/// ```
+/// use libproc::net_info::{SocketFilter, SocketProtocol, TcpSIState};
+///
+/// // Listening TCP sockets on port 8080
+/// let filter = SocketFilter::new()
+///     .protocol(SocketProtocol::Tcp)
+///     .state(TcpSIState::Listen)
+///     .port(8080);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SocketFilter {
+    protocols: Vec<SocketProtocol>,
+    states: Vec<TcpSIState>,
+    port: Option<u16>,
+}
+
+impl SocketFilter {
+    /// Create a filter that selects all sockets
+    #[must_use]
+    pub fn new() -> Self {
+        SocketFilter::default()
+    }
+
+    /// Select sockets of `protocol`. May be called more than once to select several protocols
+    #[must_use]
+    pub fn protocol(mut self, protocol: SocketProtocol) -> Self {
+        self.protocols.push(protocol);
+        self
+    }
+
+    /// Select TCP sockets in `state`. May be called more than once to select several states.
+    /// Sockets of other protocols have no state, so are not selected by a state filter
+    #[must_use]
+    pub fn state(mut self, state: TcpSIState) -> Self {
+        self.states.push(state);
+        self
+    }
+
+    /// Select IP sockets with `port` as their local or remote port
+    #[must_use]
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    /// Return true if `socket` is selected by the filter
+    #[must_use]
+    pub fn matches(&self, socket: &Socket) -> bool {
+        if !self.protocols.is_empty() && !self.protocols.contains(&socket.protocol()) {
+            return false;
+        }
+        if !self.states.is_empty()
+            && !socket
+                .state()
+                .is_some_and(|state| self.states.contains(&state))
+        {
+            return false;
+        }
+        if let Some(port) = self.port {
+            return socket
+                .inet()
+                .is_some_and(|inet| inet.local.port() == port || inet.remote.port() == port);
+        }
+        true
+    }
+}
+
+// Get the command name of a process, for reporting socket owners
+fn command_of(pid: u32) -> String {
+    i32::try_from(pid)
+        .ok()
+        .and_then(|pid| crate::libproc::proc_pid::name(pid).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// Get the inode of the network namespace of the process with `pid`, which identifies the
+/// namespace
+///
+/// # Errors
+///
+/// Will return `Err` if the process does not exist or the caller does not have permission to
+/// inspect its namespaces
+///
+/// # Examples
+///
+/// ```
+/// use libproc::net_info::net_namespace;
+/// use std::process;
+///
+/// let namespace = net_namespace(process::id() as i32).expect("Could not get namespace");
+/// println!("This process is in network namespace {}", namespace);
+/// ```
+pub fn net_namespace(pid: i32) -> Result<u64, String> {
+    proc_pid::namespace(pid, Namespace::Net)
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// Get the pids of all the processes on the system grouped by the inode of their network
+/// namespace. Processes whose namespace the caller cannot inspect are left out.
+///
+/// # Errors
+///
+/// Will return `Err` if the processes on the system cannot be listed
+///
+/// # Examples
+///
+/// ```
+/// use libproc::net_info::net_namespaces;
+///
+/// for (namespace, pids) in net_namespaces().expect("Could not list namespaces") {
+///     println!("Network namespace {}: {} processes", namespace, pids.len());
+/// }
+/// ```
+pub fn net_namespaces() -> Result<BTreeMap<u64, Vec<u32>>, String> {
+    pids_by_namespace(Namespace::Net).map_err(|e| e.to_string())
+}
+
+// Sockets keyed by their inode
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+type SocketTable = HashMap<u64, Socket>;
+
+// Read the socket tables of every network namespace, with the namespace they belong to. The
+// caller's namespace is always read, using netlink if possible. The tables of other
+// namespaces are read through the first of their processes whose tables are readable.
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+fn namespace_socket_tables() -> Result<Vec<(Option<u64>, SocketTable)>, String> {
+    let own_namespace =
+        proc_pid::read_namespace(Path::new(PROC_SELF_NET_NAMESPACE), Namespace::Net).ok();
+    let mut tables = vec![(own_namespace, local_socket_table()?)];
+
+    for (namespace, pids) in net_namespaces()? {
+        if Some(namespace) == own_namespace {
+            continue;
+        }
+        // Processes may exit while being scanned
+        if let Some(table) = pids
+            .iter()
+            .find_map(|pid| socket_table(Path::new(&format!("/proc/{pid}/net"))).ok())
+        {
+            tables.push((Some(namespace), table));
+        }
+    }
+
+    Ok(tables)
+}
+
+/// Get all the sockets on the system selected by `filter`, with the processes and file
+/// descriptors that reference them, like `ss -p` or `netstat -p`.
+///
+/// Owners are only found among the processes whose file descriptors the caller has permission
+/// to inspect, so sockets are reported as unowned if they belong to the kernel or to processes
+/// of other users when not run as root. On macOS sockets are found through their owners, so no
+/// unowned sockets are reported.
+///
+/// On Linux processes in containers have their own network namespace, with its own sockets. The
+/// processes are grouped by namespace (see [`net_namespaces`]), the socket tables of each
+/// namespace are read through one of its processes, and each socket is tagged with its
+/// namespace. Namespaces of processes the caller cannot inspect are not listed.
+///
+/// # Errors
+///
+/// Will return `Err` if the processes on the system cannot be listed, or on Linux if the
+/// socket tables of the caller's network namespace cannot be parsed
+///
+/// # Examples
+///
+/// ```
+/// use libproc::net_info::{all_sockets, SocketFilter, SocketProtocol};
+///
+/// let filter = SocketFilter::new().protocol(SocketProtocol::Tcp);
+/// for socket in all_sockets(&filter).expect("Could not list sockets") {
+///     println!("{:?} {:?} -> {:?} {:?}", socket.socket.state(), socket.socket.local_addr(),
+///              socket.socket.remote_addr(), socket.owners);
+/// }
+/// ```
+pub fn all_sockets(filter: &SocketFilter) -> Result<Vec<SystemSocket>, String> {
+    // Socket inodes are unique across network namespaces
+    let mut sockets: BTreeMap<u64, SystemSocket> = BTreeMap::new();
+
+    #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+    for (namespace, table) in namespace_socket_tables()? {
+        for (inode, socket) in table {
+            if filter.matches(&socket) {
+                sockets.insert(
+                    inode,
+                    SystemSocket {
+                        socket,
+                        owners: Vec::new(),
+                        net_namespace: namespace,
+                    },
+                );
+            }
+        }
+    }
+
+    for pid in pids_by_type(ProcFilter::All).map_err(|e| e.to_string())? {
+        let Ok(signed_pid) = i32::try_from(pid) else {
+            continue;
+        };
+        let mut command = None;
+        let mut owner = |fd| SocketOwner {
+            pid,
+            fd,
+            command: command.get_or_insert_with(|| command_of(pid)).clone(),
+        };
+
+        // Processes may exit, or not be inspectable by the caller, while being scanned
+        #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+        for (fd, inode) in socket_fds(signed_pid).unwrap_or_default() {
+            if let Some(system_socket) = sockets.get_mut(&inode) {
+                system_socket.owners.push(owner(fd));
+            }
+        }
+
+        #[cfg(target_os = "macos")]
+        for open in crate::libproc::proc_pid::sockets(signed_pid).unwrap_or_default() {
+            if filter.matches(&open.socket) {
+                sockets
+                    .entry(open.socket.inode())
+                    .or_insert_with(|| SystemSocket {
+                        socket: open.socket,
+                        owners: Vec::new(),
+                        net_namespace: None,
+                    })
+                    .owners
+                    .push(owner(open.fd));
+            }
+        }
+    }
+
+    Ok(sockets.into_values().collect())
+}
+
+/// A connection between two Unix domain sockets, and the processes holding each end
+///
+/// On Linux without `UNIX_DIAG_PEER` support, connections are only found on a best-effort
+/// basis, by pairing unbound sockets with consecutive inodes as the two ends of a
+/// `socketpair()` have. Connections to sockets bound to a path are not found then.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnixConnection {
+    /// One end of the connection. If only the other end is bound to a path, this is the client
+    /// end of a connection to a server
+    pub socket: UnixSocket,
+    /// The processes and file descriptors referencing `socket`
+    pub owners: Vec<SocketOwner>,
+    /// The other end of the connection
+    pub peer: UnixSocket,
+    /// The processes and file descriptors referencing `peer`
+    pub peer_owners: Vec<SocketOwner>,
+}
+
+// `/proc/net/unix` does not report peers, but the two ends of a `socketpair()` are created
+// together, so have consecutive inodes. Pair up unbound connected sockets of the same type that
+// do. Sockets bound to a path (including those accepted by a listener, which report its path)
+// are left unpaired, as consecutive inodes say nothing about which client they talk to.
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+fn unix_peers_by_inode(sockets: &BTreeMap<u64, UnixSocket>) -> HashMap<u64, u64> {
+    let mut peers = HashMap::new();
+    let mut connected = sockets
+        .values()
+        .filter(|unix| unix.connected && unix.path.is_none())
+        .peekable();
+    while let Some(unix) = connected.next() {
+        if let Some(next) = connected
+            .next_if(|next| next.inode == unix.inode + 1 && next.socket_type == unix.socket_type)
+        {
+            peers.insert(unix.inode, next.inode);
+            peers.insert(next.inode, unix.inode);
+        }
+    }
+    peers
+}
+
+// Find the peer of each connected Unix socket, keyed by inode
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+fn unix_peers(sockets: &BTreeMap<u64, UnixSocket>) -> HashMap<u64, u64> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    if let Ok(peers) = crate::libproc::sock_diag::unix_peers() {
+        return peers;
+    }
+    unix_peers_by_inode(sockets)
+}
+
+#[cfg(target_os = "macos")]
+fn unix_peers(sockets: &BTreeMap<u64, UnixSocket>) -> HashMap<u64, u64> {
+    sockets
+        .values()
+        .filter_map(|unix| unix.peer.map(|peer| (unix.inode, peer)))
+        .collect()
+}
+
+/// Get all the connections between Unix domain sockets on the system, and the processes at
+/// each end of them: a graph of which processes talk to each other, e.g. via D-Bus or the
+/// Docker socket.
+///
+/// On Linux peers are resolved with the `UNIX_DIAG_PEER` attribute of `NETLINK_SOCK_DIAG`. If
+/// that is not available, `/proc/net/unix` is used instead, but it does not report peers so
+/// only the unbound ends of socket pairs, which have consecutive inodes, are paired as a best
+/// effort.
+///
+/// # Errors
+///
+/// Will return `Err` if the sockets on the system cannot be listed
+///
+/// # Examples
+///
+/// ```
+/// use libproc::net_info::unix_connections;
+///
+/// for connection in unix_connections().expect("Could not list Unix sockets") {
+///     println!("{:?} <-> {:?} ({:?})", connection.owners, connection.peer_owners,
+///              connection.peer.path);
+/// }
+/// ```
+pub fn unix_connections() -> Result<Vec<UnixConnection>, String> {
+    let mut unix_sockets = BTreeMap::new();
+    let mut owners = HashMap::new();
+    for system_socket in all_sockets(&SocketFilter::new().protocol(SocketProtocol::Unix))? {
+        if let Socket::Unix(unix) = system_socket.socket {
+            owners.insert(unix.inode, system_socket.owners);
+            unix_sockets.insert(unix.inode, unix);
+        }
+    }
+
+    let peers = unix_peers(&unix_sockets);
+    let mut connections = Vec::new();
+    for (inode, unix) in &unix_sockets {
+        let Some(peer) = peers.get(inode).and_then(|peer| unix_sockets.get(peer)) else {
+            continue;
+        };
+        // Report each connection once, from the client end if only one end has a path
+        let is_client = unix.path.is_none() && peer.path.is_some();
+        let is_server = unix.path.is_some() && peer.path.is_none();
+        if is_server || (!is_client && peer.inode < unix.inode) {
+            continue;
+        }
+        connections.push(UnixConnection {
+            socket: unix.clone(),
+            owners: owners.get(inode).cloned().unwrap_or_default(),
+            peer: peer.clone(),
+            peer_owners: owners.get(&peer.inode).cloned().unwrap_or_default(),
+        });
+    }
+    Ok(connections)
+}
+
+/// Find the process and file descriptor at the other end of the Unix domain socket open on
+/// file descriptor `fd` of the process with `pid`. See [`unix_connections`] for how peers are
+/// resolved.
+///
+/// Returns `None` if the socket is not connected, its peer cannot be resolved, or the peer is
+/// not held by a process the caller can inspect.
+///
+/// # Examples
+///
+/// ```
+/// use libproc::net_info::unix_peer;
+/// use std::os::unix::io::AsRawFd;
+/// use std::os::unix::net::UnixStream;
+/// use std::process;
+///
+/// let (a, b) = UnixStream::pair().expect("Could not create socket pair");
+/// if let Some((pid, fd)) = unix_peer(process::id() as i32, a.as_raw_fd()) {
+///     println!("Peer is fd {} of process {}", fd, pid);
+/// }
+/// ```
+#[must_use]
+pub fn unix_peer(pid: i32, fd: i32) -> Option<(u32, i32)> {
+    let pid = u32::try_from(pid).ok()?;
+    let is_end = |owners: &[SocketOwner]| {
+        owners
+            .iter()
+            .any(|owner| owner.pid == pid && owner.fd == fd)
+    };
+
+    unix_connections().ok()?.into_iter().find_map(|connection| {
+        let other = if is_end(&connection.owners) {
+            connection.peer_owners
+        } else if is_end(&connection.peer_owners) {
+            connection.owners
+        } else {
+            return None;
+        };
+        other.first().map(|owner| (owner.pid, owner.fd))
+    })
+}
+
+// Compare IPv4-mapped IPv6 addresses (e.g. `::ffff:127.0.0.1`) as the IPv4 address they map
+fn canonical_ip(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(ip, IpAddr::V4),
+        IpAddr::V4(_) => ip,
+    }
+}
+
+// Return true if a socket bound to `bound` accepts packets addressed to `addr`. A socket bound
+// to the IPv6 unspecified address also accepts IPv4 traffic on dual-stack systems
+fn accepts(bound: SocketAddr, addr: SocketAddr) -> bool {
+    bound.port() == addr.port()
+        && match bound.ip() {
+            IpAddr::V6(ip) if ip.is_unspecified() => true,
+            IpAddr::V4(ip) if ip.is_unspecified() => canonical_ip(addr.ip()).is_ipv4(),
+            ip => canonical_ip(ip) == canonical_ip(addr.ip()),
+        }
+}
+
+// Return true if `socket` is waiting for connections (TCP) or datagrams (UDP) from any peer
+fn is_listening(socket: &Socket) -> bool {
+    match socket {
+        Socket::Tcp(inet) => inet.state == TcpSIState::Listen,
+        Socket::Udp(inet) => inet.remote.port() == 0,
+        _ => false,
+    }
+}
+
+/// Get the pids of the processes with a socket of `protocol` listening on `port`, on any
+/// local IPv4 or IPv6 address. A UDP socket is considered to be listening if it is not
+/// connected to a peer.
+///
+/// Only processes whose file descriptors the caller has permission to inspect are found.
+///
+/// # Errors
+///
+/// Will return `Err` if the sockets on the system cannot be listed
+///
+/// # Examples
+///
+/// ```
+/// use libproc::net_info::{pids_listening_on, SocketProtocol};
+///
+/// let pids = pids_listening_on(8080, SocketProtocol::Tcp).expect("Could not list sockets");
+/// println!("Processes listening on port 8080: {:?}", pids);
+/// ```
+pub fn pids_listening_on(port: u16, protocol: SocketProtocol) -> Result<Vec<u32>, String> {
+    let filter = SocketFilter::new().protocol(protocol).port(port);
+    let mut pids: Vec<u32> = all_sockets(&filter)?
+        .iter()
+        .filter(|socket| is_listening(&socket.socket))
+        .filter(|socket| socket.socket.local_addr().map(|addr| addr.port()) == Some(port))
+        .flat_map(|socket| socket.owners.iter().map(|owner| owner.pid))
+        .collect();
+    pids.sort_unstable();
+    pids.dedup();
+    Ok(pids)
+}
+
+/// Get the processes and file descriptors owning the TCP or UDP socket with `local` address.
+///
+/// If `remote` is given, the socket connected from `local` to `remote` is found, otherwise the
+/// socket listening on `local`, which may be bound to the unspecified address (e.g. `0.0.0.0`
+/// or `::`). IPv4 addresses match the equivalent IPv4-mapped IPv6 addresses
+/// (e.g. `::ffff:127.0.0.1`) used by dual-stack sockets.
+///
+/// # Errors
+///
+/// Will return `Err` if the sockets on the system cannot be listed
+///
+/// # Examples
+///
+/// ```
+/// use libproc::net_info::owner_of;
+/// use std::net::TcpListener;
+///
+/// let listener = TcpListener::bind("127.0.0.1:0").expect("Could not bind");
+/// let local = listener.local_addr().expect("Could not get local address");
+/// for owner in owner_of(local, None).expect("Could not list sockets") {
+///     println!("{} is listening on fd {} of {} ({})", local, owner.fd, owner.pid,
+///              owner.command);
+/// }
+/// ```
+pub fn owner_of(local: SocketAddr, remote: Option<SocketAddr>) -> Result<Vec<SocketOwner>, String> {
+    let filter = SocketFilter::new()
+        .protocol(SocketProtocol::Tcp)
+        .protocol(SocketProtocol::Udp)
+        .port(local.port());
+
+    Ok(all_sockets(&filter)?
+        .into_iter()
+        .filter(|socket| {
+            let Some(inet) = socket.socket.inet() else {
+                return false;
+            };
+            match remote {
+                Some(remote) => {
+                    inet.local.port() == local.port()
+                        && canonical_ip(inet.local.ip()) == canonical_ip(local.ip())
+                        && inet.remote.port() == remote.port()
+                        && canonical_ip(inet.remote.ip()) == canonical_ip(remote.ip())
+                }
+                None => is_listening(&socket.socket) && accepts(inet.local, local),
+            }
+        })
+        .flat_map(|socket| socket.owners)
+        .collect())
+}
+
+// Flag in `insi_vflag` set when the socket addresses are IPv6
+#[cfg(target_os = "macos")]
+const INI_IPV6: u8 = 0x2;
+
+#[cfg(target_os = "macos")]
+impl InSockInfo {
+    // Ports are in network byte order in the low 16 bits
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn port(port: c_int) -> u16 {
+        u16::from_be(port as u16)
+    }
+
+    fn addr(&self, addr: &InSIAddr) -> IpAddr {
+        // The `insi_vflag` says which member of the union is valid
+        if self.insi_vflag & INI_IPV6 != 0 {
+            IpAddr::V6(Ipv6Addr::from(unsafe { addr.ina_6.s6_addr }))
+        } else {
+            IpAddr::V4(Ipv4Addr::from(u32::from_be(unsafe {
+                addr.ina_46.i46a_addr4.s_addr
+            })))
+        }
+    }
+
+    /// The local address and port of the socket
+    #[must_use]
+    pub fn local_addr(&self) -> SocketAddr {
+        SocketAddr::new(self.addr(&self.insi_laddr), Self::port(self.insi_lport))
+    }
+
+    /// The remote address and port of the socket
+    #[must_use]
+    pub fn remote_addr(&self) -> SocketAddr {
+        SocketAddr::new(self.addr(&self.insi_faddr), Self::port(self.insi_fport))
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl Socket {
+    /// Convert the [`SocketInfo`] returned by `pidfdinfo::<SocketFDInfo>()` into a `Socket`,
+    /// returning `None` for kinds of socket that are not represented
+    #[must_use]
+    #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+    pub fn from_socket_info(info: &SocketInfo) -> Option<Socket> {
+        let inet = |ini: &InSockInfo, state: TcpSIState| InetSocket {
+            local: ini.local_addr(),
+            remote: ini.remote_addr(),
+            state,
+            tx_queue: info.soi_snd.sbi_cc,
+            rx_queue: info.soi_rcv.sbi_cc,
+            uid: info.soi_stat.vst_uid,
+            inode: info.soi_so,
+        };
+
+        match SocketInfoKind::from(info.soi_kind) {
+            SocketInfoKind::Tcp => {
+                // The union member is valid for the kind of socket
+                let tcp = unsafe { info.soi_proto.pri_tcp };
+                Some(Socket::Tcp(inet(
+                    &tcp.tcpsi_ini,
+                    TcpSIState::from(tcp.tcpsi_state),
+                )))
+            }
+            SocketInfoKind::In => {
+                let ini = unsafe { info.soi_proto.pri_in };
+                let remote = ini.remote_addr();
+                let state = if remote.port() == 0 {
+                    TcpSIState::Closed
+                } else {
+                    TcpSIState::Established
+                };
+                if info.soi_protocol == libc::IPPROTO_UDP {
+                    Some(Socket::Udp(inet(&ini, state)))
+                } else {
+                    Some(Socket::Raw(inet(&ini, state)))
+                }
+            }
+            SocketInfoKind::Un => {
+                let un = unsafe { info.soi_proto.pri_un };
+                let sun_path = unsafe { un.unsi_addr.ua_sun.sun_path };
+                let path: Vec<u8> = sun_path
+                    .iter()
+                    .take_while(|c| **c != 0)
+                    .map(|c| *c as u8)
+                    .collect();
+                Some(Socket::Unix(UnixSocket {
+                    socket_type: UnixSocketType::from(info.soi_type),
+                    listening: info.soi_options & libc::SO_ACCEPTCONN as c_short != 0,
+                    connected: un.unsi_conn_so != 0,
+                    path: if path.is_empty() {
+                        None
+                    } else {
+                        Some(String::from_utf8_lossy(&path).into_owned())
+                    },
+                    inode: info.soi_so,
+                    peer: Some(un.unsi_conn_so).filter(|peer| *peer != 0),
+                }))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+const PROC_NET: &str = "/proc/net";
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+const PROC_SELF_NET_NAMESPACE: &str = "/proc/self/ns/net";
+
+// IP protocol numbers, as used by `INET_DIAG`
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+const IPPROTO_TCP: u8 = 6;
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+const IPPROTO_UDP: u8 = 17;
+
+// Names of the files in a /proc/<pid>/net directory listing each kind of socket
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+const PROC_NET_TCP: [&str; 2] = ["tcp", "tcp6"];
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+const PROC_NET_UDP: [&str; 2] = ["udp", "udp6"];
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+const PROC_NET_UDPLITE: [&str; 2] = ["udplite", "udplite6"];
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+const PROC_NET_RAW: [&str; 2] = ["raw", "raw6"];
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+const PROC_NET_UNIX: &str = "unix";
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+const PROC_NET_NETLINK: &str = "netlink";
+
+// Flag set in /proc/net/unix for a listening socket (`__SO_ACCEPTCON`)
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+const UNIX_FLAG_LISTENING: u32 = 1 << 16;
+// Socket state in /proc/net/unix for a connected socket (`SS_CONNECTED`)
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+const UNIX_STATE_CONNECTED: u8 = 3;
+
+// Addresses in /proc/net/{tcp,udp,raw}* are printed as 32 bit words in host byte order
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+fn parse_proc_net_addr(field: &str) -> Option<SocketAddr> {
+    let (addr, port) = field.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+    match addr.len() {
+        8 => {
+            let word = u32::from_str_radix(addr, 16).ok()?;
+            Some(SocketAddr::V4(SocketAddrV4::new(
+                Ipv4Addr::from(word.to_ne_bytes()),
+                port,
+            )))
+        }
+        32 => {
+            let mut octets = [0u8; 16];
+            for (i, chunk) in octets.chunks_exact_mut(4).enumerate() {
+                let word = u32::from_str_radix(addr.get(i * 8..i * 8 + 8)?, 16).ok()?;
+                chunk.copy_from_slice(&word.to_ne_bytes());
+            }
+            Some(SocketAddr::V6(SocketAddrV6::new(
+                Ipv6Addr::from(octets),
+                port,
+                0,
+                0,
+            )))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+fn parse_proc_net_inet_line(line: &str) -> Option<InetSocket> {
+    let mut fields = line.split_ascii_whitespace();
+    fields.next()?.strip_suffix(':')?;
+    let local = parse_proc_net_addr(fields.next()?)?;
+    let remote = parse_proc_net_addr(fields.next()?)?;
+    let state = u8::from_str_radix(fields.next()?, 16).ok()?;
+    let (tx_queue, rx_queue) = fields.next()?.split_once(':')?;
+    // Skip the timer and retransmit fields
+    let uid = fields.nth(2)?.parse().ok()?;
+    // Skip the timeout field
+    let inode = fields.nth(1)?.parse().ok()?;
+
+    Some(InetSocket {
+        local,
+        remote,
+        state: TcpSIState::from_linux(state),
+        tx_queue: u32::from_str_radix(tx_queue, 16).ok()?,
+        rx_queue: u32::from_str_radix(rx_queue, 16).ok()?,
+        uid,
+        inode,
+    })
+}
+
+/// Parse the contents of one of the `/proc/net/{tcp,tcp6,udp,udp6,raw,raw6}` files
+///
+/// # Errors
+///
+/// Will return `Err` if the contents cannot be read or a line cannot be parsed
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+pub fn parse_proc_net_inet<R: BufRead>(reader: R) -> Result<Vec<InetSocket>, String> {
+    let mut sockets = Vec::new();
+    // The first line is a header
+    for line in reader.lines().skip(1) {
+        let line = line.map_err(|e| format!("Could not read socket table: {e}"))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        sockets.push(
+            parse_proc_net_inet_line(&line)
+                .ok_or_else(|| format!("Could not parse socket table line '{line}'"))?,
+        );
+    }
+    Ok(sockets)
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+fn parse_proc_net_unix_line(line: &str) -> Option<UnixSocket> {
+    // The fields before the inode are fixed width, separated by single spaces
+    let mut fields = line.splitn(7, ' ');
+    fields.next()?.strip_suffix(':')?;
+    // Skip the reference count and protocol fields
+    let flags = u32::from_str_radix(fields.nth(2)?, 16).ok()?;
+    let socket_type = c_int::from_str_radix(fields.next()?, 16).ok()?;
+    let state = u8::from_str_radix(fields.next()?, 16).ok()?;
+    // The inode is padded to a column, and followed by a space and the path as it is, which may
+    // itself contain runs of spaces, if the socket is bound
+    let rest = fields.next()?.trim_start();
+    let (inode, path) = rest.split_once(' ').unwrap_or((rest, ""));
+    let inode = inode.parse().ok()?;
+
+    Some(UnixSocket {
+        socket_type: UnixSocketType::from(socket_type),
+        listening: flags & UNIX_FLAG_LISTENING != 0,
+        connected: state == UNIX_STATE_CONNECTED,
+        path: if path.is_empty() {
+            None
+        } else {
+            Some(path.to_owned())
+        },
+        inode,
+        peer: None,
+    })
+}
+
+/// Parse the contents of the `/proc/net/unix` file
+///
+/// # Errors
+///
+/// Will return `Err` if the contents cannot be read or a line cannot be parsed
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+pub fn parse_proc_net_unix<R: BufRead>(reader: R) -> Result<Vec<UnixSocket>, String> {
+    let mut sockets = Vec::new();
+    for line in reader.lines().skip(1) {
+        let line = line.map_err(|e| format!("Could not read socket table: {e}"))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        sockets.push(
+            parse_proc_net_unix_line(&line)
+                .ok_or_else(|| format!("Could not parse socket table line '{line}'"))?,
+        );
+    }
+    Ok(sockets)
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+fn parse_proc_net_netlink_line(line: &str) -> Option<NetlinkSocket> {
+    let mut fields = line.split_ascii_whitespace().skip(1);
+    let protocol = fields.next()?.parse().ok()?;
+    let port_id = fields.next()?.parse().ok()?;
+    let groups = u32::from_str_radix(fields.next()?, 16).ok()?;
+    let rx_queue = fields.next()?.parse().ok()?;
+    let tx_queue = fields.next()?.parse().ok()?;
+    // Skip the dump, locks and drops fields
+    let inode = fields.nth(3)?.parse().ok()?;
+
+    Some(NetlinkSocket {
+        protocol,
+        port_id,
+        groups,
+        tx_queue,
+        rx_queue,
+        inode,
+    })
+}
+
+/// Parse the contents of the `/proc/net/netlink` file
+///
+/// # Errors
+///
+/// Will return `Err` if the contents cannot be read or a line cannot be parsed
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+pub fn parse_proc_net_netlink<R: BufRead>(reader: R) -> Result<Vec<NetlinkSocket>, String> {
+    let mut sockets = Vec::new();
+    for line in reader.lines().skip(1) {
+        let line = line.map_err(|e| format!("Could not read socket table: {e}"))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        sockets.push(
+            parse_proc_net_netlink_line(&line)
+                .ok_or_else(|| format!("Could not parse socket table line '{line}'"))?,
+        );
+    }
+    Ok(sockets)
+}
+
+// Open one of the socket tables in `net_dir`. Tables for protocols that are not available
+// (e.g. IPv6 is disabled) do not exist, so are treated as empty
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+fn open_socket_table(net_dir: &Path, name: &str) -> Option<BufReader<File>> {
+    File::open(net_dir.join(name)).ok().map(BufReader::new)
+}
+
+// Wraps the sockets read from an IP socket table as the `Socket` variant for their protocol
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+type InetSocketKind = fn(InetSocket) -> Socket;
+
+// Read the sockets listed in the IP socket tables `inet_tables`, and the Unix and netlink socket
+// tables, of a procfs `net` directory
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+fn read_socket_tables(
+    net_dir: &Path,
+    inet_tables: &[(&[&str], InetSocketKind)],
+) -> Result<HashMap<u64, Socket>, String> {
+    let mut sockets = HashMap::new();
+
+    for (names, wrap) in inet_tables {
+        for name in *names {
+            if let Some(reader) = open_socket_table(net_dir, name) {
+                for inet in parse_proc_net_inet(reader)? {
+                    sockets.insert(inet.inode, wrap(inet));
+                }
+            }
+        }
+    }
+
+    if let Some(reader) = open_socket_table(net_dir, PROC_NET_UNIX) {
+        for unix in parse_proc_net_unix(reader)? {
+            sockets.insert(unix.inode, Socket::Unix(unix));
+        }
+    }
+
+    if let Some(reader) = open_socket_table(net_dir, PROC_NET_NETLINK) {
+        for netlink in parse_proc_net_netlink(reader)? {
+            sockets.insert(netlink.inode, Socket::Netlink(netlink));
+        }
+    }
+
+    Ok(sockets)
+}
+
+/// Read all the sockets listed in the socket tables of a procfs `net` directory, such as
+/// `/proc/net` or `/proc/<pid>/net`, keyed by their inode
+///
+/// # Errors
+///
+/// Will return `Err` if one of the socket tables cannot be parsed
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+pub fn socket_table(net_dir: &Path) -> Result<HashMap<u64, Socket>, String> {
+    read_socket_tables(
+        net_dir,
+        &[
+            (&PROC_NET_TCP, Socket::Tcp),
+            (&PROC_NET_UDP, Socket::Udp),
+            (&PROC_NET_UDPLITE, Socket::Udp),
+            (&PROC_NET_RAW, Socket::Raw),
+        ],
+    )
+}
+
+// Read all the sockets in the caller's network namespace, getting TCP and UDP sockets with
+// `INET_DIAG` if possible, as it is much faster than parsing `/proc/net` on busy hosts
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+fn local_socket_table() -> Result<HashMap<u64, Socket>, String> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    if let (Ok(tcp), Ok(udp)) = (
+        sock_diag::inet_sockets(IPPROTO_TCP),
+        sock_diag::inet_sockets(IPPROTO_UDP),
+    ) {
+        let mut sockets = read_socket_tables(
+            Path::new(PROC_NET),
+            &[
+                (&PROC_NET_UDPLITE, Socket::Udp),
+                (&PROC_NET_RAW, Socket::Raw),
+            ],
+        )?;
+        for info in tcp {
+            sockets.insert(info.socket.inode, Socket::Tcp(info.socket));
+        }
+        for info in udp {
+            sockets.insert(info.socket.inode, Socket::Udp(info.socket));
+        }
+        return Ok(sockets);
+    }
+
+    socket_table(Path::new(PROC_NET))
+}
+
+/// Get the inode of the socket open on a file descriptor from the target of its
+/// `/proc/<pid>/fd/<fd>` link, which has the form "socket:[inode]"
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+#[must_use]
+pub fn socket_inode(link: &Path) -> Option<u64> {
+    link.to_str()?
+        .strip_prefix("socket:[")?
+        .strip_suffix(']')?
+        .parse()
+        .ok()
+}
+
+/// Get the file descriptors of the process with `pid` that have sockets open on them, and the
+/// inodes of those sockets
+///
+/// # Errors
+///
+/// Will return `Err` if the process does not exist or the caller does not have permission to
+/// read its file descriptors
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+pub fn socket_fds(pid: i32) -> Result<Vec<(i32, u64)>, String> {
+    Ok(list_fds(pid)?
+        .into_iter()
+        .filter_map(|fd| {
+            let link = fs::read_link(format!("/proc/{pid}/fd/{fd}")).ok()?;
+            socket_inode(&link).map(|inode| (fd, inode))
+        })
+        .collect())
+}
+
+#[cfg(all(
+    test,
+    any(target_os = "linux", target_os = "redox", target_os = "android")
+))]
+#[allow(clippy::cast_possible_wrap)]
+mod test {
+    use super::{
+        accepts, all_sockets, inet_sockets, net_namespace, net_namespaces, owner_of,
+        parse_proc_net_inet, parse_proc_net_netlink, parse_proc_net_unix, pids_listening_on,
+        socket_inode, unix_connections, unix_peer, unix_peers_by_inode, InetSocket, Socket,
+        SocketFilter, SocketOwner, SocketProtocol, TcpSIState, UnixSocketType,
+    };
+    use std::net::{SocketAddr, TcpListener, TcpStream};
+    use std::os::unix::io::AsRawFd;
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::Path;
+    use std::process;
+
+    fn tcp(local: &str, remote: &str, state: TcpSIState) -> Socket {
+        Socket::Tcp(InetSocket {
+            local: local.parse().expect("Invalid address"),
+            remote: remote.parse().expect("Invalid address"),
+            state,
+            tx_queue: 0,
+            rx_queue: 0,
+            uid: 0,
+            inode: 1,
+        })
+    }
+
+    #[test]
+    fn parse_tcp() {
+        let table = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:1F90 00000000:0000 0A 00000000:00000002 00:00000000 00000000  1000        0 662 1 00000000e58995d0 100 0 0 10 0
+   1: 0F02000A:D2C8 2E08A8C0:01BB 01 0000001C:00000000 01:00000014 00000000  1000        0 920 1 000000004b228ec7 20 4 30 10 -1
+";
+        let sockets = parse_proc_net_inet(table.as_bytes()).expect("Could not parse tcp table");
+        assert_eq!(sockets.len(), 2);
+        assert_eq!(
+            sockets[0].local,
+            "127.0.0.1:8080"
+                .parse::<SocketAddr>()
+                .expect("Invalid address")
+        );
+        assert_eq!(sockets[0].state, TcpSIState::Listen);
+        assert_eq!(sockets[0].rx_queue, 2);
+        assert_eq!(sockets[0].uid, 1000);
+        assert_eq!(sockets[0].inode, 662);
+        assert_eq!(
+            sockets[1].local,
+            "10.0.2.15:53960"
+                .parse::<SocketAddr>()
+                .expect("Invalid address")
+        );
+        assert_eq!(
+            sockets[1].remote,
+            "192.168.8.46:443"
+                .parse::<SocketAddr>()
+                .expect("Invalid address")
+        );
+        assert_eq!(sockets[1].state, TcpSIState::Established);
+        assert_eq!(sockets[1].tx_queue, 0x1c);
+    }
+
+    #[test]
+    fn parse_tcp6() {
+        let table = "  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000000000000000000001000000:0016 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 17890 1 0000000000000000 100 0 0 10 0
+   1: 0000000000000000FFFF00000100007F:0050 0000000000000000FFFF00000100007F:A4B2 06 00000000:00000000 03:00000FA0 00000000     0        0 0 3 0000000000000000
+";
+        let sockets = parse_proc_net_inet(table.as_bytes()).expect("Could not parse tcp6 table");
+        assert_eq!(
+            sockets[0].local,
+            "[::1]:22".parse::<SocketAddr>().expect("Invalid address")
+        );
+        assert_eq!(
+            sockets[1].local,
+            "[::ffff:127.0.0.1]:80"
+                .parse::<SocketAddr>()
+                .expect("Invalid address")
+        );
+        assert_eq!(sockets[1].state, TcpSIState::TimeWait);
+    }
+
+    #[test]
+    fn parse_unix() {
+        let table = "Num       RefCount Protocol Flags    Type St Inode Path
+00000000c0cc3e2b: 00000002 00000000 00010000 0001 01 16928 /run/systemd/private
+00000000755604ac: 00000003 00000000 00000000 0002 03 16929
+0000000012345678: 00000003 00000000 00000000 0005 03 16930 @/tmp/.X11-unix/X0
+000000009abcdef0: 00000002 00000000 00010000 0001 01   812 /run/two  spaces\tand tab
+";
+        let sockets = parse_proc_net_unix(table.as_bytes()).expect("Could not parse unix table");
+        assert_eq!(sockets.len(), 4);
+        assert!(sockets[0].listening);
+        assert!(!sockets[0].connected);
+        assert_eq!(sockets[0].socket_type, UnixSocketType::Stream);
+        assert_eq!(sockets[0].path.as_deref(), Some("/run/systemd/private"));
+        assert_eq!(sockets[1].socket_type, UnixSocketType::Datagram);
+        assert!(sockets[1].connected);
+        assert_eq!(sockets[1].path, None);
+        assert_eq!(sockets[2].socket_type, UnixSocketType::SeqPacket);
+        assert_eq!(sockets[2].path.as_deref(), Some("@/tmp/.X11-unix/X0"));
+        // Short inodes are padded, and paths are kept as they are
+        assert_eq!(sockets[3].inode, 812);
+        assert_eq!(
+            sockets[3].path.as_deref(),
+            Some("/run/two  spaces\tand tab")
+        );
+    }
+
+    #[test]
+    fn parse_netlink() {
+        let table = "sk               Eth Pid        Groups   Rmem     Wmem     Dump  Locks    Drops    Inode
+0000000056792cc0 0   1234       00000551 0        0        0     2        0        4
+";
+        let sockets =
+            parse_proc_net_netlink(table.as_bytes()).expect("Could not parse netlink table");
+        assert_eq!(sockets[0].protocol, 0);
+        assert_eq!(sockets[0].port_id, 1234);
+        assert_eq!(sockets[0].groups, 0x551);
+        assert_eq!(sockets[0].inode, 4);
+    }
+
+    #[test]
+    fn parse_invalid_table() {
+        assert!(parse_proc_net_inet("header\n   0: garbage\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn inode_from_link() {
+        assert_eq!(socket_inode(Path::new("socket:[16928]")), Some(16928));
+        assert_eq!(socket_inode(Path::new("pipe:[16928]")), None);
+        assert_eq!(socket_inode(Path::new("/dev/null")), None);
+    }
+
+    #[test]
+    fn filter_sockets() {
+        let listening = tcp("0.0.0.0:8080", "0.0.0.0:0", TcpSIState::Listen);
+        let connected = tcp("10.0.0.1:40000", "10.0.0.2:443", TcpSIState::Established);
+
+        assert!(SocketFilter::new().matches(&listening));
+        assert!(SocketFilter::new()
+            .protocol(SocketProtocol::Tcp)
+            .matches(&listening));
+        assert!(!SocketFilter::new()
+            .protocol(SocketProtocol::Udp)
+            .matches(&listening));
+        assert!(SocketFilter::new()
+            .state(TcpSIState::Listen)
+            .matches(&listening));
+        assert!(!SocketFilter::new()
+            .state(TcpSIState::Listen)
+            .matches(&connected));
+        assert!(SocketFilter::new().port(8080).matches(&listening));
+        assert!(SocketFilter::new().port(443).matches(&connected));
+        assert!(!SocketFilter::new().port(443).matches(&listening));
+    }
+
+    #[test]
+    fn all_sockets_finds_owner() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Could not bind listener");
+        let local = listener.local_addr().expect("Could not get local address");
+        let filter = SocketFilter::new()
+            .protocol(SocketProtocol::Tcp)
+            .state(TcpSIState::Listen)
+            .port(local.port());
+
+        let sockets = all_sockets(&filter).expect("all_sockets() failed");
+        let found = sockets
+            .iter()
+            .find(|socket| socket.socket.local_addr() == Some(local))
+            .expect("Listening socket not found");
+        assert!(!found.is_unowned());
+        assert_eq!(
+            found.net_namespace,
+            net_namespace(process::id() as i32).ok()
+        );
+        assert!(found
+            .owners
+            .iter()
+            .any(|owner| owner.pid == process::id() && !owner.command.is_empty()));
+    }
+
+    #[test]
+    fn accepts_addresses() {
+        let addr = |addr: &str| addr.parse::<SocketAddr>().expect("Invalid address");
+        assert!(accepts(addr("127.0.0.1:80"), addr("127.0.0.1:80")));
+        assert!(!accepts(addr("127.0.0.1:80"), addr("127.0.0.1:81")));
+        assert!(!accepts(addr("127.0.0.1:80"), addr("10.0.0.1:80")));
+        assert!(accepts(addr("0.0.0.0:80"), addr("10.0.0.1:80")));
+        assert!(accepts(addr("0.0.0.0:80"), addr("[::ffff:10.0.0.1]:80")));
+        assert!(!accepts(addr("0.0.0.0:80"), addr("[::1]:80")));
+        assert!(accepts(addr("[::]:80"), addr("10.0.0.1:80")));
+        assert!(accepts(addr("[::]:80"), addr("[::1]:80")));
+        assert!(accepts(addr("[::ffff:127.0.0.1]:80"), addr("127.0.0.1:80")));
+        assert!(accepts(addr("127.0.0.1:80"), addr("[::ffff:127.0.0.1]:80")));
+    }
+
+    #[test]
+    fn pids_listening_on_port() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Could not bind listener");
+        let port = listener
+            .local_addr()
+            .expect("Could not get local address")
+            .port();
+        let pids =
+            pids_listening_on(port, SocketProtocol::Tcp).expect("pids_listening_on() failed");
+        assert_eq!(pids, vec![process::id()]);
+        assert!(pids_listening_on(port, SocketProtocol::Udp)
+            .expect("pids_listening_on() failed")
+            .is_empty());
+    }
+
+    #[test]
+    fn owner_of_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Could not bind listener");
+        let server = listener.local_addr().expect("Could not get local address");
+        let client = TcpStream::connect(server).expect("Could not connect");
+        let client_addr = client.local_addr().expect("Could not get local address");
+
+        let listening = owner_of(server, None).expect("owner_of() failed");
+        assert_eq!(listening.len(), 1);
+        assert_eq!(listening[0].pid, process::id());
+
+        let connected = owner_of(client_addr, Some(server)).expect("owner_of() failed");
+        assert_eq!(connected.len(), 1);
+        assert_eq!(connected[0].pid, process::id());
+
+        // The same lookup via the IPv4-mapped IPv6 addresses
+        let mapped = |addr: SocketAddr| match addr {
+            SocketAddr::V4(v4) => SocketAddr::new(v4.ip().to_ipv6_mapped().into(), v4.port()),
+            SocketAddr::V6(_) => addr,
+        };
+        let connected =
+            owner_of(mapped(client_addr), Some(mapped(server))).expect("owner_of() failed");
+        assert_eq!(connected.len(), 1);
+    }
+
+    #[test]
+    fn pair_unix_sockets_by_inode() {
+        let table = "Num       RefCount Protocol Flags    Type St Inode Path
+0000000000000001: 00000003 00000000 00000000 0001 03 100
+0000000000000002: 00000003 00000000 00000000 0001 03 101
+0000000000000003: 00000003 00000000 00000000 0001 03 103
+0000000000000004: 00000003 00000000 00000000 0002 03 104
+0000000000000005: 00000002 00000000 00010000 0001 01 105 /run/listening
+0000000000000006: 00000003 00000000 00000000 0001 03 106 /run/listening
+0000000000000007: 00000003 00000000 00000000 0001 03 107
+";
+        let sockets = parse_proc_net_unix(table.as_bytes())
+            .expect("Could not parse unix table")
+            .into_iter()
+            .map(|unix| (unix.inode, unix))
+            .collect();
+        let peers = unix_peers_by_inode(&sockets);
+        assert_eq!(peers.len(), 2);
+        assert_eq!(peers.get(&100), Some(&101));
+        assert_eq!(peers.get(&101), Some(&100));
+        // An accepted socket, which reports the listener's path, is not paired with a client
+        assert_eq!(peers.get(&106), None);
+    }
+
+    #[test]
+    fn unix_peer_of_socket_pair() {
+        let (a, b) = UnixStream::pair().expect("Could not create socket pair");
+        let pid = process::id() as i32;
+        assert_eq!(
+            unix_peer(pid, a.as_raw_fd()),
+            Some((process::id(), b.as_raw_fd()))
+        );
+        assert_eq!(
+            unix_peer(pid, b.as_raw_fd()),
+            Some((process::id(), a.as_raw_fd()))
+        );
+    }
+
+    #[test]
+    fn unix_connection_to_server() {
+        let dir = tempfile::tempdir().expect("Could not create temporary directory");
+        let path = dir.path().join("server.sock");
+        let listener = UnixListener::bind(&path).expect("Could not bind listener");
+        let client = UnixStream::connect(&path).expect("Could not connect");
+        let (server, _) = listener.accept().expect("Could not accept connection");
+
+        let connections = unix_connections().expect("unix_connections() failed");
+        let is_fd = |owners: &[SocketOwner], fd: i32| {
+            owners
+                .iter()
+                .any(|owner| owner.pid == process::id() && owner.fd == fd)
+        };
+        match connections
+            .iter()
+            .find(|connection| is_fd(&connection.owners, client.as_raw_fd()))
+        {
+            Some(connection) => {
+                assert!(is_fd(&connection.peer_owners, server.as_raw_fd()));
+                assert_eq!(connection.peer.path.as_deref(), path.to_str());
+            }
+            // Without sock_diag the peers of accepted connections cannot be resolved
+            None => println!("test skipped as sock_diag is not available"),
+        }
+    }
+
+    #[test]
+    fn inet_sockets_of_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Could not bind listener");
+        let server = listener.local_addr().expect("Could not get local address");
+        let client = TcpStream::connect(server).expect("Could not connect");
+        let client_addr = client.local_addr().expect("Could not get local address");
+
+        let sockets = inet_sockets(SocketProtocol::Tcp).expect("inet_sockets() failed");
+        let listening = sockets
+            .iter()
+            .find(|info| info.socket.local == server && info.socket.state == TcpSIState::Listen)
+            .expect("Listening socket not found");
+        let connected = sockets
+            .iter()
+            .find(|info| info.socket.local == client_addr && info.socket.remote == server)
+            .expect("Connected socket not found");
+        assert_eq!(connected.socket.state, TcpSIState::Established);
+        // Metrics are only available from netlink
+        if let Some(tcp_info) = &connected.tcp_info {
+            assert_eq!(tcp_info.state, TcpSIState::Established);
+            assert!(tcp_info.snd_cwnd > 0);
+            assert!(listening.tcp_info.is_some());
+        }
+    }
+
+    #[test]
+    fn inet_sockets_of_unix_protocol() {
+        assert!(inet_sockets(SocketProtocol::Unix).is_err());
+    }
+
+    #[test]
+    fn own_net_namespace_group() {
+        let namespace = net_namespace(process::id() as i32).expect("net_namespace() failed");
+        let namespaces = net_namespaces().expect("net_namespaces() failed");
+        assert!(namespaces
+            .get(&namespace)
+            .is_some_and(|pids| pids.contains(&process::id())));
+    }
+}
@@ -0,0 +1,483 @@
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::iter::FromIterator;
+use std::os::unix::fs::FileExt;
+
+// Bit layout of a `/proc/<pid>/pagemap` entry, see
+// <https://www.kernel.org/doc/Documentation/vm/pagemap.txt>
+const PM_PFN_MASK: u64 = (1 << 55) - 1;
+const PM_SWAP_TYPE_MASK: u64 = (1 << 5) - 1;
+const PM_SWAP_OFFSET_SHIFT: u64 = 5;
+const PM_SOFT_DIRTY: u64 = 1 << 55;
+const PM_EXCLUSIVE: u64 = 1 << 56;
+const PM_FILE_OR_SHARED_ANON: u64 = 1 << 61;
+const PM_SWAPPED: u64 = 1 << 62;
+const PM_PRESENT: u64 = 1 << 63;
+
+// Size in bytes of each entry in the pagemap file
+const PM_ENTRY_SIZE: u64 = 8;
+// How many pagemap entries to read from the kernel with each call
+const PM_ENTRIES_PER_READ: usize = 512;
+
+/// A region of a process's virtual address space, as listed in `/proc/<pid>/maps`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryRegion {
+    /// First address of the region
+    pub start: u64,
+    /// First address after the end of the region
+    pub end: u64,
+    /// Permissions of the region, e.g. "r-xp"
+    pub perms: String,
+    /// Offset into the mapped file
+    pub offset: u64,
+    /// Major and minor device numbers of the mapped file
+    pub dev: (u32, u32),
+    /// Inode of the mapped file, 0 for anonymous regions
+    pub inode: u64,
+    /// Path of the mapped file, or pseudo-path such as `[heap]` or `[stack]`, if any
+    pub pathname: Option<String>,
+}
+
+impl MemoryRegion {
+    /// Size of the region in bytes
+    #[must_use]
+    pub fn size(&self) -> u64 {
+        self.end - self.start
+    }
+
+    /// Parse a single line of a `/proc/<pid>/maps` (or `smaps` header) file, returning `None`
+    /// if it is not a region line.
+    #[must_use]
+    pub fn parse(line: &str) -> Option<MemoryRegion> {
+        let mut fields = line.splitn(6, ' ');
+        let (start, end) = fields.next()?.split_once('-')?;
+        let perms = fields.next()?;
+        let offset = fields.next()?;
+        let (major, minor) = fields.next()?.split_once(':')?;
+        let inode = fields.next()?;
+        // The pathname is padded to a column and may itself contain runs of spaces, so take
+        // the rest of the line after the inode as it is
+        let pathname = fields.next().unwrap_or_default().trim_start();
+
+        Some(MemoryRegion {
+            start: u64::from_str_radix(start, 16).ok()?,
+            end: u64::from_str_radix(end, 16).ok()?,
+            perms: perms.to_owned(),
+            offset: u64::from_str_radix(offset, 16).ok()?,
+            dev: (
+                u32::from_str_radix(major, 16).ok()?,
+                u32::from_str_radix(minor, 16).ok()?,
+            ),
+            inode: inode.parse().ok()?,
+            pathname: if pathname.is_empty() {
+                None
+            } else {
+                Some(pathname.to_owned())
+            },
+        })
+    }
+}
+
+/// Information about a single page of a process's virtual memory, decoded from its entry
+/// in `/proc/<pid>/pagemap`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct PageInfo {
+    /// Virtual address of the start of the page
+    pub address: u64,
+    /// The page is present in RAM
+    pub present: bool,
+    /// The page has been swapped out
+    pub swapped: bool,
+    /// The page has been written to since the soft-dirty bits were last cleared
+    pub soft_dirty: bool,
+    /// The page is mapped exclusively by this process
+    pub exclusive: bool,
+    /// The page is a file page or shared anonymous page
+    pub file_or_shared_anon: bool,
+    /// Page Frame Number of a present page. The kernel only reports this to
+    /// privileged (`CAP_SYS_ADMIN`) callers, for others it will be `None`
+    pub pfn: Option<u64>,
+    /// Swap type of a swapped page
+    pub swap_type: Option<u8>,
+    /// Offset into swap of a swapped page
+    pub swap_offset: Option<u64>,
+}
+
+impl PageInfo {
+    /// Decode a raw 64-bit pagemap `entry` for the page starting at `address`
+    #[must_use]
+    pub fn from_entry(address: u64, entry: u64) -> PageInfo {
+        let present = entry & PM_PRESENT != 0;
+        let swapped = entry & PM_SWAPPED != 0;
+        let pfn = entry & PM_PFN_MASK;
+
+        PageInfo {
+            address,
+            present,
+            swapped,
+            soft_dirty: entry & PM_SOFT_DIRTY != 0,
+            exclusive: entry & PM_EXCLUSIVE != 0,
+            file_or_shared_anon: entry & PM_FILE_OR_SHARED_ANON != 0,
+            pfn: if present && pfn != 0 { Some(pfn) } else { None },
+            // masked to 5 bits so cannot truncate
+            #[allow(clippy::cast_possible_truncation)]
+            swap_type: if swapped {
+                Some((pfn & PM_SWAP_TYPE_MASK) as u8)
+            } else {
+                None
+            },
+            swap_offset: if swapped {
+                Some(pfn >> PM_SWAP_OFFSET_SHIFT)
+            } else {
+                None
+            },
+        }
+    }
+}
+
+/// Summary counts of the pages in a range of virtual memory
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PageSummary {
+    /// Total number of pages in the range
+    pub pages: u64,
+    /// Number of pages present in RAM
+    pub present: u64,
+    /// Number of pages swapped out
+    pub swapped: u64,
+    /// Number of soft-dirty pages
+    pub soft_dirty: u64,
+    /// Number of pages mapped exclusively by this process
+    pub exclusive: u64,
+    /// Number of file pages or shared anonymous pages
+    pub file_or_shared_anon: u64,
+}
+
+impl PageSummary {
+    /// Add the information about one page to the summary
+    pub fn add(&mut self, page: &PageInfo) {
+        self.pages += 1;
+        self.present += u64::from(page.present);
+        self.swapped += u64::from(page.swapped);
+        self.soft_dirty += u64::from(page.soft_dirty);
+        self.exclusive += u64::from(page.exclusive);
+        self.file_or_shared_anon += u64::from(page.file_or_shared_anon);
+    }
+}
+
+impl<'a> FromIterator<&'a PageInfo> for PageSummary {
+    fn from_iter<I: IntoIterator<Item = &'a PageInfo>>(iter: I) -> Self {
+        let mut summary = PageSummary::default();
+        for page in iter {
+            summary.add(page);
+        }
+        summary
+    }
+}
+
+/// An iterator over the [`PageInfo`] of each page in a range of a process's virtual memory.
+///
+/// Entries are read from `/proc/<pid>/pagemap` in batches as the iterator advances.
+pub struct PageInfoIter {
+    pagemap: File,
+    page_size: u64,
+    address: u64,
+    end: u64,
+    entries: Vec<u64>,
+    next_entry: usize,
+}
+
+impl PageInfoIter {
+    // Read the next batch of entries starting at `self.address`
+    fn fill(&mut self) -> Result<(), String> {
+        let remaining = (self.end - self.address).div_ceil(self.page_size);
+        let count = usize::try_from(remaining).map_or(PM_ENTRIES_PER_READ, |remaining| {
+            remaining.min(PM_ENTRIES_PER_READ)
+        });
+        // `count` is at most PM_ENTRIES_PER_READ
+        #[allow(clippy::cast_possible_truncation)]
+        let mut buf = vec![0u8; count * PM_ENTRY_SIZE as usize];
+        let offset = self.address / self.page_size * PM_ENTRY_SIZE;
+        self.pagemap
+            .read_exact_at(&mut buf, offset)
+            .map_err(|e| format!("Could not read pagemap at address {:#x}: {e}", self.address))?;
+
+        self.entries = buf
+            .chunks_exact(8)
+            .map(|bytes| {
+                let mut entry = [0u8; 8];
+                entry.copy_from_slice(bytes);
+                u64::from_ne_bytes(entry)
+            })
+            .collect();
+        self.next_entry = 0;
+        Ok(())
+    }
+}
+
+impl Iterator for PageInfoIter {
+    type Item = Result<PageInfo, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.address >= self.end {
+            return None;
+        }
+
+        if self.next_entry >= self.entries.len() {
+            if let Err(e) = self.fill() {
+                // Stop iterating after reporting the error
+                self.address = self.end;
+                return Some(Err(e));
+            }
+        }
+
+        let page = PageInfo::from_entry(self.address, self.entries[self.next_entry]);
+        self.next_entry += 1;
+        self.address += self.page_size;
+        Some(Ok(page))
+    }
+}
+
+/// Return the size of a page of virtual memory on this system, in bytes
+#[must_use]
+pub fn page_size() -> u64 {
+    let size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    u64::try_from(size).unwrap_or(4096)
+}
+
+/// Get the list of mapped regions of the virtual address space of the process with `pid`,
+/// from `/proc/<pid>/maps`
+///
+/// # Errors
+///
+/// Will return `Err` if the process does not exist or its maps file cannot be read
+///
+/// # Examples
+///
+/// ```
+/// use libproc::page_info::memory_regions;
+/// use std::process;
+///
+/// let pid = process::id() as i32;
+/// for region in memory_regions(pid).expect("Could not get memory regions") {
+///     println!("{:#x}-{:#x} {} {:?}", region.start, region.end, region.perms, region.pathname);
+/// }
+/// ```
+pub fn memory_regions(pid: i32) -> Result<Vec<MemoryRegion>, String> {
+    let filename = format!("/proc/{pid}/maps");
+    let file = File::open(&filename)
+        .map_err(|e| format!("Could not open /proc file '{filename}': {e}"))?;
+
+    let mut regions = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|e| format!("Could not read '{filename}': {e}"))?;
+        if let Some(region) = MemoryRegion::parse(&line) {
+            regions.push(region);
+        }
+    }
+
+    Ok(regions)
+}
+
+/// Get an iterator over the [`PageInfo`] of each page in the range of virtual addresses
+/// `start..end` of the process with `pid`. `start` is rounded down to a page boundary.
+///
+/// Reading `/proc/<pid>/pagemap` of another process requires the same permissions as
+/// `ptrace`-ing it, and page frame numbers are only reported to privileged callers.
+///
+/// # Errors
+///
+/// Will return `Err` if the range is empty or the pagemap file of the process cannot be opened.
+/// Errors reading individual pages are returned by the iterator.
+///
+/// # Examples
+///
+/// ```
+/// use libproc::page_info::{memory_regions, pages};
+/// use std::process;
+///
+/// let pid = process::id() as i32;
+/// let regions = memory_regions(pid).expect("Could not get memory regions");
+/// if let Some(stack) = regions.iter().find(|r| r.pathname.as_deref() == Some("[stack]")) {
+///     for page in pages(pid, stack.start, stack.end).expect("Could not read pagemap").flatten() {
+///         if page.present {
+///             println!("Page at {:#x} is present", page.address);
+///         }
+///     }
+/// }
+/// ```
+pub fn pages(pid: i32, start: u64, end: u64) -> Result<PageInfoIter, String> {
+    if start >= end {
+        return Err(format!("Invalid address range {start:#x}-{end:#x}"));
+    }
+
+    let filename = format!("/proc/{pid}/pagemap");
+    let pagemap = File::open(&filename)
+        .map_err(|e| format!("Could not open /proc file '{filename}': {e}"))?;
+    let page_size = page_size();
+
+    Ok(PageInfoIter {
+        pagemap,
+        page_size,
+        address: start - start % page_size,
+        end,
+        entries: Vec::new(),
+        next_entry: 0,
+    })
+}
+
+/// Get an iterator over the [`PageInfo`] of each page in a [`MemoryRegion`] of the process with
+/// `pid`
+///
+/// # Errors
+///
+/// See [`pages`]
+pub fn region_pages(pid: i32, region: &MemoryRegion) -> Result<PageInfoIter, String> {
+    pages(pid, region.start, region.end)
+}
+
+/// Get a [`PageSummary`] of the pages in a [`MemoryRegion`] of the process with `pid`
+///
+/// # Errors
+///
+/// Will return `Err` if the pagemap file of the process cannot be opened or read
+///
+/// # Examples
+///
+/// ```
+/// use libproc::page_info::{memory_regions, region_summary};
+/// use std::process;
+///
+/// let pid = process::id() as i32;
+/// for region in memory_regions(pid).expect("Could not get memory regions") {
+///     if let Ok(summary) = region_summary(pid, &region) {
+///         println!("{:?}: {} of {} pages present, {} swapped", region.pathname,
+///                  summary.present, summary.pages, summary.swapped);
+///     }
+/// }
+/// ```
+pub fn region_summary(pid: i32, region: &MemoryRegion) -> Result<PageSummary, String> {
+    let mut summary = PageSummary::default();
+    for page in region_pages(pid, region)? {
+        summary.add(&page?);
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+#[allow(clippy::cast_possible_wrap)]
+mod test {
+    use super::{
+        memory_regions, page_size, pages, region_summary, MemoryRegion, PageInfo, PageSummary,
+    };
+    use std::process;
+
+    #[test]
+    fn parse_file_region() {
+        let region = MemoryRegion::parse(
+            "55f39f719000-55f39f71f000 r-xp 00002000 fe:00 317783                     /usr/bin/head",
+        )
+        .expect("Could not parse region");
+        assert_eq!(region.start, 0x55f3_9f71_9000);
+        assert_eq!(region.end, 0x55f3_9f71_f000);
+        assert_eq!(region.size(), 0x6000);
+        assert_eq!(region.perms, "r-xp");
+        assert_eq!(region.offset, 0x2000);
+        assert_eq!(region.dev, (0xfe, 0));
+        assert_eq!(region.inode, 317_783);
+        assert_eq!(region.pathname.as_deref(), Some("/usr/bin/head"));
+    }
+
+    #[test]
+    fn parse_anonymous_region() {
+        let region = MemoryRegion::parse("7ffd1c3e0000-7ffd1c3e2000 rw-p 00000000 00:00 0 ")
+            .expect("Could not parse region");
+        assert_eq!(region.inode, 0);
+        assert_eq!(region.pathname, None);
+
+        let region = MemoryRegion::parse("7ffd1c3e0000-7ffd1c3e2000 rw-p 00000000 00:00 0")
+            .expect("Could not parse region");
+        assert_eq!(region.pathname, None);
+    }
+
+    #[test]
+    fn parse_path_with_spaces() {
+        let region =
+            MemoryRegion::parse("7f00-8000 r--s 00000000 08:01 42    /tmp/my file (deleted)")
+                .expect("Could not parse region");
+        assert_eq!(region.pathname.as_deref(), Some("/tmp/my file (deleted)"));
+
+        let region = MemoryRegion::parse(
+            "7f00-8000 r-xp 00000000 08:01 43                         /opt/two  spaces/lib.so",
+        )
+        .expect("Could not parse region");
+        assert_eq!(region.pathname.as_deref(), Some("/opt/two  spaces/lib.so"));
+    }
+
+    #[test]
+    fn parse_invalid_region() {
+        assert!(MemoryRegion::parse("Rss:                 12 kB").is_none());
+    }
+
+    #[test]
+    fn decode_present_page() {
+        let page = PageInfo::from_entry(0x1000, (1 << 63) | (1 << 56) | (1 << 55) | 0x1234);
+        assert!(page.present);
+        assert!(!page.swapped);
+        assert!(page.exclusive);
+        assert!(page.soft_dirty);
+        assert!(!page.file_or_shared_anon);
+        assert_eq!(page.pfn, Some(0x1234));
+        assert_eq!(page.swap_type, None);
+    }
+
+    #[test]
+    fn decode_unprivileged_present_page() {
+        let page = PageInfo::from_entry(0x1000, (1 << 63) | (1 << 61));
+        assert!(page.present);
+        assert!(page.file_or_shared_anon);
+        assert_eq!(page.pfn, None);
+    }
+
+    #[test]
+    fn decode_swapped_page() {
+        let page = PageInfo::from_entry(0x2000, (1 << 62) | (0xabc << 5) | 3);
+        assert!(!page.present);
+        assert!(page.swapped);
+        assert_eq!(page.pfn, None);
+        assert_eq!(page.swap_type, Some(3));
+        assert_eq!(page.swap_offset, Some(0xabc));
+    }
+
+    #[test]
+    fn summary_counts() {
+        let pages = [
+            PageInfo::from_entry(0x1000, 1 << 63),
+            PageInfo::from_entry(0x2000, 1 << 62),
+            PageInfo::from_entry(0x3000, 0),
+        ];
+        let summary: PageSummary = pages.iter().collect();
+        assert_eq!(summary.pages, 3);
+        assert_eq!(summary.present, 1);
+        assert_eq!(summary.swapped, 1);
+    }
+
+    #[test]
+    fn invalid_range() {
+        assert!(pages(process::id() as i32, 0x2000, 0x1000).is_err());
+    }
+
+    #[test]
+    fn stack_pages_present() {
+        let pid = process::id() as i32;
+        let regions = memory_regions(pid).expect("memory_regions() failed");
+        let stack = regions
+            .iter()
+            .find(|r| r.pathname.as_deref() == Some("[stack]"))
+            .expect("No stack region found");
+        let summary = region_summary(pid, stack).expect("region_summary() failed");
+        assert_eq!(summary.pages, stack.size() / page_size());
+        assert!(summary.present > 0, "No stack pages present");
+    }
+}
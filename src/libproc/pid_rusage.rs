@@ -4,8 +4,15 @@ use crate::libproc::helpers;
 #[cfg(target_os = "macos")]
 use libc::c_void;
 
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+use std::str::FromStr;
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+use std::time::{Duration, Instant};
+
 #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
 use crate::libproc::helpers::{parse_memory_string, procfile_field};
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+use crate::libproc::proc_pid::io_stats;
 #[cfg(target_os = "macos")]
 use crate::osx_libproc_bindings::proc_pid_rusage;
 
@@ -356,6 +363,166 @@ impl PIDRUsage for RUsageInfoV4 {
     }
 }
 
+/// I/O counters of a process, from `/proc/<pid>/io`
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IOStats {
+    /// Bytes read by the process using any read-like system call, including from page cache
+    pub rchar: u64,
+    /// Bytes written by the process using any write-like system call
+    pub wchar: u64,
+    /// Number of read-like system calls
+    pub syscr: u64,
+    /// Number of write-like system calls
+    pub syscw: u64,
+    /// Bytes the process caused to be fetched from the storage layer
+    pub read_bytes: u64,
+    /// Bytes the process caused to be sent to the storage layer
+    pub write_bytes: u64,
+    /// Bytes the process caused to not be written to storage, by truncating dirty page cache
+    pub cancelled_write_bytes: u64,
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+impl FromStr for IOStats {
+    type Err = String;
+
+    /// Parse the contents of a `/proc/<pid>/io` file
+    fn from_str(contents: &str) -> Result<Self, Self::Err> {
+        let mut stats = IOStats::default();
+        for line in contents.lines() {
+            if let Some((name, value)) = line.split_once(':') {
+                let value: u64 = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("Could not parse value of I/O counter '{name}'"))?;
+                match name {
+                    "rchar" => stats.rchar = value,
+                    "wchar" => stats.wchar = value,
+                    "syscr" => stats.syscr = value,
+                    "syscw" => stats.syscw = value,
+                    "read_bytes" => stats.read_bytes = value,
+                    "write_bytes" => stats.write_bytes = value,
+                    "cancelled_write_bytes" => stats.cancelled_write_bytes = value,
+                    _ => {}
+                }
+            }
+        }
+        Ok(stats)
+    }
+}
+
+/// Per-second I/O rates of a process, calculated from two [`IOStats`] readings
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct IORates {
+    /// Bytes read per second using read-like system calls
+    pub rchar_per_sec: f64,
+    /// Bytes written per second using write-like system calls
+    pub wchar_per_sec: f64,
+    /// Read-like system calls per second
+    pub syscr_per_sec: f64,
+    /// Write-like system calls per second
+    pub syscw_per_sec: f64,
+    /// Bytes read per second from the storage layer
+    pub read_bytes_per_sec: f64,
+    /// Bytes written per second to the storage layer
+    pub write_bytes_per_sec: f64,
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+impl IORates {
+    /// Calculate the rates from an `earlier` and a `later` reading taken `elapsed` apart.
+    /// Counters that went backwards (e.g. the pid was reused) are treated as zero.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn between(earlier: &IOStats, later: &IOStats, elapsed: Duration) -> IORates {
+        let secs = elapsed.as_secs_f64();
+        let rate = |before: u64, after: u64| {
+            if secs > 0.0 {
+                after.saturating_sub(before) as f64 / secs
+            } else {
+                0.0
+            }
+        };
+
+        IORates {
+            rchar_per_sec: rate(earlier.rchar, later.rchar),
+            wchar_per_sec: rate(earlier.wchar, later.wchar),
+            syscr_per_sec: rate(earlier.syscr, later.syscr),
+            syscw_per_sec: rate(earlier.syscw, later.syscw),
+            read_bytes_per_sec: rate(earlier.read_bytes, later.read_bytes),
+            write_bytes_per_sec: rate(earlier.write_bytes, later.write_bytes),
+        }
+    }
+}
+
+/// Samples the I/O counters of a process and turns consecutive readings into [`IORates`]
+///
+/// # Examples
+///
+/// ```
+/// use libproc::libproc::pid_rusage::IOSampler;
+/// use std::process;
+/// use std::thread;
+/// use std::time::Duration;
+///
+/// let mut sampler = IOSampler::new(process::id() as i32).expect("Could not read I/O counters");
+/// thread::sleep(Duration::from_millis(100));
+/// if let Ok(rates) = sampler.sample() {
+///     println!("Reading {} bytes/s, writing {} bytes/s", rates.read_bytes_per_sec,
+///              rates.write_bytes_per_sec);
+/// }
+/// ```
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+pub struct IOSampler {
+    pid: i32,
+    last: IOStats,
+    last_time: Instant,
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+impl IOSampler {
+    /// Create a sampler for the process with `pid`, taking the first reading
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the I/O counters of the process cannot be read
+    pub fn new(pid: i32) -> Result<IOSampler, String> {
+        Ok(IOSampler {
+            pid,
+            last: io_stats(pid)?,
+            last_time: Instant::now(),
+        })
+    }
+
+    /// The pid of the process being sampled
+    #[must_use]
+    pub fn pid(&self) -> i32 {
+        self.pid
+    }
+
+    /// The most recent reading of the I/O counters
+    #[must_use]
+    pub fn last(&self) -> &IOStats {
+        &self.last
+    }
+
+    /// Take a new reading, returning the rates since the previous one
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the I/O counters of the process cannot be read, e.g. it has exited
+    pub fn sample(&mut self) -> Result<IORates, String> {
+        let stats = io_stats(self.pid)?;
+        let now = Instant::now();
+        let rates = IORates::between(&self.last, &stats, now - self.last_time);
+        self.last = stats;
+        self.last_time = now;
+        Ok(rates)
+    }
+}
+
 #[cfg(target_os = "macos")]
 /// Returns the information about resources of the process that match pid passed in.
 ///
@@ -440,4 +607,63 @@ mod test {
         let usage: RUsageInfoV0 = pidrusage(std::process::id() as i32).expect("pidrusage() failed");
         assert!(usage.ri_resident_size > 0, "Resident size reports 0");
     }
+
+    #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+    mod linux {
+        use crate::libproc::pid_rusage::{IORates, IOSampler, IOStats};
+        use std::time::Duration;
+
+        const PROC_IO: &str = "rchar: 3980
+wchar: 120
+syscr: 9
+syscw: 2
+read_bytes: 4096
+write_bytes: 8192
+cancelled_write_bytes: 0
+";
+
+        #[test]
+        fn parse_io_stats() {
+            let stats: IOStats = PROC_IO.parse().expect("Could not parse I/O stats");
+            assert_eq!(stats.rchar, 3980);
+            assert_eq!(stats.wchar, 120);
+            assert_eq!(stats.syscr, 9);
+            assert_eq!(stats.syscw, 2);
+            assert_eq!(stats.read_bytes, 4096);
+            assert_eq!(stats.write_bytes, 8192);
+            assert_eq!(stats.cancelled_write_bytes, 0);
+        }
+
+        #[test]
+        fn parse_invalid_io_stats() {
+            assert!("rchar: lots".parse::<IOStats>().is_err());
+        }
+
+        #[test]
+        fn rates_between_readings() {
+            let earlier = IOStats {
+                read_bytes: 1000,
+                write_bytes: 5000,
+                ..IOStats::default()
+            };
+            let later = IOStats {
+                read_bytes: 3000,
+                write_bytes: 4000,
+                ..IOStats::default()
+            };
+            let rates = IORates::between(&earlier, &later, Duration::from_secs(2));
+            assert!((rates.read_bytes_per_sec - 1000.0).abs() < f64::EPSILON);
+            assert!(rates.write_bytes_per_sec.abs() < f64::EPSILON);
+        }
+
+        #[test]
+        fn sample_self() {
+            let mut sampler =
+                IOSampler::new(std::process::id() as i32).expect("IOSampler::new() failed");
+            let first = *sampler.last();
+            let _ = std::fs::read_to_string("/proc/self/status");
+            sampler.sample().expect("sample() failed");
+            assert!(sampler.last().rchar > first.rchar);
+        }
+    }
 }
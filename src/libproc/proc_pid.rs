@@ -1,12 +1,20 @@
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+use std::collections::BTreeMap;
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+use std::convert::TryFrom;
 use std::env;
 #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
 use std::ffi::CString;
 #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
 use std::fs;
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+use std::io;
 #[cfg(target_os = "macos")]
 use std::mem;
 #[cfg(target_os = "macos")]
 use std::mem::size_of;
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+use std::path::Path;
 use std::path::PathBuf;
 
 #[cfg(target_os = "macos")]
@@ -18,7 +26,23 @@ use libc::PATH_MAX;
 
 #[cfg(target_os = "macos")]
 use crate::libproc::bsd_info::BSDInfo;
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+use crate::libproc::cgroup::{self, Cgroup};
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+use crate::libproc::file_info::{self, FileProgress, OpenFile};
+#[cfg(target_os = "macos")]
+use crate::libproc::file_info::{pidfdinfo, ListFDs, ProcFDType};
 use crate::libproc::helpers;
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+use crate::libproc::lock_info::{self, FileLock};
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+use crate::libproc::mount_info::{self, MountInfo};
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+use crate::libproc::net_info::{self, OpenSocket};
+#[cfg(target_os = "macos")]
+use crate::libproc::net_info::{OpenSocket, Socket, SocketFDInfo};
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+use crate::libproc::pid_rusage::IOStats;
 #[cfg(target_os = "macos")]
 use crate::libproc::task_info::{TaskAllInfo, TaskInfo};
 #[cfg(target_os = "macos")]
@@ -655,6 +679,741 @@ pub fn cwdself() -> Result<PathBuf, String> {
     env::current_dir().map_err(|e| e.to_string())
 }
 
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// Get the I/O counters of the process with the provided pid, from `/proc/<pid>/io`.
+///
+/// Use an [`IOSampler`][crate::libproc::pid_rusage::IOSampler] to turn readings into rates.
+///
+/// # Errors
+///
+/// An `Err` is returned if the process with PID `pid` does not exist, or its `io` file in the
+/// procfs file system cannot be read (it is only readable by the owner of the process) or parsed
+///
+/// # Examples
+///
+/// ```
+/// use libproc::libproc::proc_pid::io_stats;
+/// use std::process;
+///
+/// match io_stats(process::id() as i32) {
+///     Ok(stats) => println!("Read {} bytes, written {} bytes", stats.read_bytes, stats.write_bytes),
+///     Err(err) => eprintln!("Error: {}", err)
+/// }
+/// ```
+pub fn io_stats(pid: pid_t) -> Result<IOStats, String> {
+    let filename = format!("/proc/{pid}/io");
+    fs::read_to_string(&filename)
+        .map_err(|e| format!("Could not read /proc file '{filename}': {e}"))?
+        .parse()
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// Get the files open in the process with the provided pid: for each file descriptor the path it
+/// resolves to, its access mode and offset, and whether the file has been deleted while open.
+///
+/// File descriptors that are closed while the list is being built are left out.
+///
+/// # Errors
+///
+/// An `Err` is returned if the process with PID `pid` does not exist, or the caller does not have
+/// permission to read its file descriptors
+///
+/// # Examples
+///
+/// ```
+/// use libproc::libproc::proc_pid::open_files;
+/// use std::process;
+///
+/// for file in open_files(process::id() as i32).expect("Could not get open files") {
+///     println!("{}: {} {:?}{}", file.fd, file.path.display(), file.mode,
+///              if file.deleted { " (deleted)" } else { "" });
+/// }
+/// ```
+pub fn open_files(pid: pid_t) -> Result<Vec<OpenFile>, String> {
+    let mut files = Vec::new();
+    for fd in file_info::list_fds(pid)? {
+        // The fd may have been closed since listing them
+        if let (Ok(link), Ok(info)) = (
+            fs::read_link(format!("/proc/{pid}/fd/{fd}")),
+            file_info::fdinfo(pid, fd),
+        ) {
+            files.push(OpenFile::new(pid, fd, link, &info));
+        }
+    }
+
+    Ok(files)
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// Get the progress of the process with the provided pid through each regular file it has open:
+/// the current file offset compared to the size of the file.
+///
+/// Use a [`FileProgressSampler`][crate::libproc::file_info::FileProgressSampler] to also get the
+/// throughput and estimated time remaining.
+///
+/// # Errors
+///
+/// An `Err` is returned if the process with PID `pid` does not exist, or the caller does not have
+/// permission to read its file descriptors
+///
+/// # Examples
+///
+/// ```
+/// use libproc::libproc::proc_pid::file_progress;
+/// use std::process;
+///
+/// for file in file_progress(process::id() as i32).expect("Could not get file progress") {
+///     println!("{}: {:.1}%", file.path.display(), file.percent());
+/// }
+/// ```
+pub fn file_progress(pid: pid_t) -> Result<Vec<FileProgress>, String> {
+    Ok(open_files(pid)?
+        .into_iter()
+        .filter_map(|file| {
+            let metadata = fs::metadata(format!("/proc/{pid}/fd/{}", file.fd)).ok()?;
+            if !metadata.is_file() {
+                return None;
+            }
+            Some(FileProgress {
+                fd: file.fd,
+                path: file.path,
+                mode: file.mode,
+                position: file.offset,
+                size: metadata.len(),
+            })
+        })
+        .collect())
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// Get the file locks held by the process with the provided pid, with any lock requests blocked
+/// waiting for them. See [`all_locks`][crate::libproc::lock_info::all_locks].
+///
+/// # Errors
+///
+/// An `Err` is returned if `/proc/locks` cannot be read or parsed
+///
+/// # Examples
+///
+/// ```
+/// use libproc::libproc::proc_pid::locks;
+/// use std::process;
+///
+/// for lock in locks(process::id() as i32).expect("Could not get file locks") {
+///     println!("{:?} {:?} lock on {:?}", lock.kind, lock.access, lock.path);
+/// }
+/// ```
+pub fn locks(pid: pid_t) -> Result<Vec<FileLock>, String> {
+    let pid = u32::try_from(pid).map_err(|_| format!("Invalid pid {pid}"))?;
+    Ok(lock_info::all_locks()?
+        .into_iter()
+        .filter(|lock| lock.pid == Some(pid))
+        .collect())
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// Get the mount table of the process with the provided pid, from `/proc/<pid>/mountinfo`.
+///
+/// Processes in other mount namespaces (e.g. in containers) see a different set of mounts to
+/// the caller, so paths such as those returned by [`pidcwd`] and [`pidpath`] need to be
+/// interpreted using the mounts of the process.
+///
+/// # Errors
+///
+/// An `Err` is returned if the process with PID `pid` does not exist, or its mountinfo file in
+/// the procfs file system cannot be read or parsed
+///
+/// # Examples
+///
+/// ```
+/// use libproc::libproc::proc_pid::mounts;
+/// use std::process;
+///
+/// for mount in mounts(process::id() as i32).expect("Could not get mounts") {
+///     println!("{} on {} type {}", mount.source.unwrap_or_default(),
+///              mount.mount_point.display(), mount.fs_type);
+/// }
+/// ```
+pub fn mounts(pid: pid_t) -> Result<Vec<MountInfo>, String> {
+    let filename = format!("/proc/{pid}/mountinfo");
+    let file = fs::File::open(&filename)
+        .map_err(|e| format!("Could not open /proc file '{filename}': {e}"))?;
+    mount_info::parse_mountinfo(io::BufReader::new(file))
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// Get the cgroups of the process with the provided pid, from `/proc/<pid>/cgroup`. There is
+/// one for each cgroup v1 hierarchy, and one for the cgroup v2 unified hierarchy (see
+/// [`Cgroup::is_unified`]) if it is mounted.
+///
+/// # Errors
+///
+/// An `Err` is returned if the process with PID `pid` does not exist, or its cgroup file in
+/// the procfs file system cannot be read or parsed
+///
+/// # Examples
+///
+/// ```
+/// use libproc::libproc::proc_pid::cgroups;
+/// use std::process;
+///
+/// for cgroup in cgroups(process::id() as i32).expect("Could not get cgroups") {
+///     println!("{:?}: {}", cgroup.controllers, cgroup.path.display());
+/// }
+/// ```
+pub fn cgroups(pid: pid_t) -> Result<Vec<Cgroup>, String> {
+    let filename = format!("/proc/{pid}/cgroup");
+    let file = fs::File::open(&filename)
+        .map_err(|e| format!("Could not open /proc file '{filename}': {e}"))?;
+    cgroup::parse_proc_cgroup(io::BufReader::new(file))
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// Translate a `path` as seen by the process with the provided pid into a path the caller can
+/// use to reach the same file, via the process's root directory `/proc/<pid>/root`, e.g. when
+/// the process is in a different mount namespace or has been `chroot`-ed.
+///
+/// The path is translated as it is, without resolving it. An absolute symbolic link, or a `..`
+/// past the process's root, met while the kernel resolves the returned path is resolved
+/// against the caller's root directory rather than the process's, so may reach a different
+/// file than the process would.
+///
+/// Relative paths are taken as relative to the current working directory of the process.
+/// Accessing files through the returned path requires the same permissions as `ptrace`-ing
+/// the process.
+///
+/// # Examples
+///
+/// ```
+/// use libproc::libproc::proc_pid::translate_path;
+/// use std::path::Path;
+///
+/// let path = translate_path(1, Path::new("/etc/hostname"));
+/// assert_eq!(path, Path::new("/proc/1/root/etc/hostname"));
+/// ```
+#[must_use]
+pub fn translate_path(pid: pid_t, path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        let mut translated = PathBuf::from(format!("/proc/{pid}/root"));
+        // Pushing an absolute path would replace the root, so push each component
+        translated.extend(path.components().skip(1));
+        translated
+    } else {
+        PathBuf::from(format!("/proc/{pid}/cwd")).join(path)
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// Get the current OOM killer score of the process with the provided pid. The process with the
+/// highest score is the one the kernel will kill first when the system runs out of memory.
+///
+/// # Errors
+///
+/// An `Err` is returned if the process with PID `pid` does not exist, or its `oom_score` file
+/// in the procfs file system cannot be read or parsed
+///
+/// # Examples
+///
+/// ```
+/// use libproc::libproc::proc_pid::oom_score;
+/// use std::process;
+///
+/// match oom_score(process::id() as i32) {
+///     Ok(score) => println!("OOM score of this process is {}", score),
+///     Err(err) => eprintln!("Error: {}", err)
+/// }
+/// ```
+pub fn oom_score(pid: pid_t) -> Result<u32, String> {
+    helpers::procfile_value(&format!("/proc/{pid}/oom_score"))
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// Get the adjustment (in the range -1000 to 1000) applied to the OOM killer score of the
+/// process with the provided pid. A value of -1000 disables OOM killing of the process.
+///
+/// # Errors
+///
+/// An `Err` is returned if the process with PID `pid` does not exist, or its `oom_score_adj` file
+/// in the procfs file system cannot be read or parsed
+///
+/// # Examples
+///
+/// ```
+/// use libproc::libproc::proc_pid::oom_score_adj;
+/// use std::process;
+///
+/// match oom_score_adj(process::id() as i32) {
+///     Ok(adj) => println!("OOM score adjustment of this process is {}", adj),
+///     Err(err) => eprintln!("Error: {}", err)
+/// }
+/// ```
+pub fn oom_score_adj(pid: pid_t) -> Result<i32, String> {
+    helpers::procfile_value(&format!("/proc/{pid}/oom_score_adj"))
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// Set the adjustment applied to the OOM killer score of the process with the provided pid.
+///
+/// `adj` must be in the range -1000 (never kill) to 1000 (always kill first). Lowering the value
+/// below its previous minimum requires the `CAP_SYS_RESOURCE` capability.
+///
+/// # Errors
+///
+/// An `Err` is returned if `adj` is out of range, the process with PID `pid` does not exist or
+/// its `oom_score_adj` file cannot be written to
+///
+/// # Examples
+///
+/// ```
+/// use libproc::libproc::proc_pid::{oom_score_adj, set_oom_score_adj};
+/// use std::process;
+///
+/// let pid = process::id() as i32;
+/// // Make this process a more likely candidate to be killed when out of memory
+/// if set_oom_score_adj(pid, 500).is_ok() {
+///     assert_eq!(oom_score_adj(pid), Ok(500));
+/// }
+/// ```
+pub fn set_oom_score_adj(pid: pid_t, adj: i32) -> Result<(), String> {
+    if !(-1000..=1000).contains(&adj) {
+        return Err(format!(
+            "OOM score adjustment {adj} is not in the range -1000 to 1000"
+        ));
+    }
+
+    let filename = format!("/proc/{pid}/oom_score_adj");
+    fs::write(&filename, adj.to_string())
+        .map_err(|e| format!("Could not write to /proc file '{filename}': {e}"))
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// A type of Linux namespace, as listed in `/proc/<pid>/ns`
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Namespace {
+    /// Cgroup root directory
+    Cgroup,
+    /// System V IPC and POSIX message queues
+    Ipc,
+    /// Mount points
+    Mnt,
+    /// Network devices, stacks and ports
+    Net,
+    /// Process IDs
+    Pid,
+    /// Process IDs of the children the process creates from now on
+    PidForChildren,
+    /// Boot and monotonic clocks
+    Time,
+    /// Boot and monotonic clocks of the children the process creates from now on
+    TimeForChildren,
+    /// User and group IDs
+    User,
+    /// Hostname and NIS domain name
+    Uts,
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+impl Namespace {
+    /// All the types of namespace
+    pub const ALL: [Namespace; 10] = [
+        Namespace::Cgroup,
+        Namespace::Ipc,
+        Namespace::Mnt,
+        Namespace::Net,
+        Namespace::Pid,
+        Namespace::PidForChildren,
+        Namespace::Time,
+        Namespace::TimeForChildren,
+        Namespace::User,
+        Namespace::Uts,
+    ];
+
+    /// The name of the namespace's file in `/proc/<pid>/ns`, e.g. "`pid_for_children`"
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Namespace::Cgroup => "cgroup",
+            Namespace::Ipc => "ipc",
+            Namespace::Mnt => "mnt",
+            Namespace::Net => "net",
+            Namespace::Pid => "pid",
+            Namespace::PidForChildren => "pid_for_children",
+            Namespace::Time => "time",
+            Namespace::TimeForChildren => "time_for_children",
+            Namespace::User => "user",
+            Namespace::Uts => "uts",
+        }
+    }
+
+    // The type in the target of the namespace's link, where the `_for_children` variants
+    // link to a namespace of the plain type
+    fn link_type(self) -> &'static str {
+        match self {
+            Namespace::PidForChildren => "pid",
+            Namespace::TimeForChildren => "time",
+            _ => self.name(),
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+// Parse the target of a `/proc/<pid>/ns/<namespace>` link, which has the form "net:[inode]"
+fn parse_namespace_link(link: &Path, namespace: Namespace) -> Option<u64> {
+    link.to_str()?
+        .strip_prefix(namespace.link_type())?
+        .strip_prefix(":[")?
+        .strip_suffix(']')?
+        .parse()
+        .ok()
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+// Read the inode of `namespace` from its link `filename`, e.g. "/proc/self/ns/net"
+pub(crate) fn read_namespace(filename: &Path, namespace: Namespace) -> Result<u64, String> {
+    let link = fs::read_link(filename)
+        .map_err(|e| format!("Could not read link '{}': {e}", filename.display()))?;
+    parse_namespace_link(&link, namespace)
+        .ok_or_else(|| format!("Could not parse namespace '{}'", link.display()))
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// Get the inode of the `namespace` of the process with the provided pid, which identifies
+/// the namespace. Processes with the same inode for a type of namespace share it.
+///
+/// # Errors
+///
+/// An `Err` is returned if the process with PID `pid` does not exist, the kernel does not
+/// support `namespace`, or the caller does not have permission to inspect its namespaces
+///
+/// # Examples
+///
+/// ```
+/// use libproc::libproc::proc_pid::{namespace, Namespace};
+/// use std::process;
+///
+/// let inode = namespace(process::id() as i32, Namespace::Mnt).expect("Could not get namespace");
+/// println!("This process is in mount namespace {}", inode);
+/// ```
+pub fn namespace(pid: pid_t, namespace: Namespace) -> Result<u64, String> {
+    read_namespace(
+        Path::new(&format!("/proc/{pid}/ns/{}", namespace.name())),
+        namespace,
+    )
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// Get the inodes of all the namespaces of the process with the provided pid, from
+/// `/proc/<pid>/ns`. Types of namespace the kernel does not support are left out.
+///
+/// # Errors
+///
+/// An `Err` is returned if the process with PID `pid` does not exist, or the caller does not
+/// have permission to inspect its namespaces
+///
+/// # Examples
+///
+/// ```
+/// use libproc::libproc::proc_pid::{namespaces, Namespace};
+/// use std::process;
+///
+/// let own = namespaces(process::id() as i32).expect("Could not get namespaces");
+/// let init = namespaces(1).unwrap_or_default();
+/// if own.get(&Namespace::Pid) != init.get(&Namespace::Pid) {
+///     println!("This process is running in a container");
+/// }
+/// ```
+pub fn namespaces(pid: pid_t) -> Result<BTreeMap<Namespace, u64>, String> {
+    let dir = format!("/proc/{pid}/ns");
+    let entries =
+        fs::read_dir(&dir).map_err(|e| format!("Could not read directory '{dir}': {e}"))?;
+    let mut namespaces = BTreeMap::new();
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Could not read directory '{dir}': {e}"))?;
+        let Some(namespace) = Namespace::ALL
+            .iter()
+            .copied()
+            .find(|namespace| entry.file_name() == namespace.name())
+        else {
+            continue;
+        };
+        namespaces.insert(namespace, read_namespace(&entry.path(), namespace)?);
+    }
+    Ok(namespaces)
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// The ids of a process in each of the pid namespaces it is visible in, from the `NSpid`,
+/// `NStgid`, `NSpgid` and `NSsid` fields of `/proc/<pid>/status`.
+///
+/// Each list starts with the id in the pid namespace of the procfs mount the process was read
+/// from (usually the caller's), followed by the id in each nested namespace down to the
+/// process's own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamespacePids {
+    /// Process ids
+    pub pid: Vec<u32>,
+    /// Thread group ids, the process ids of multi-threaded processes
+    pub tgid: Vec<u32>,
+    /// Process group ids, or 0 if the process group is not visible in a namespace
+    pub pgid: Vec<u32>,
+    /// Session ids, or 0 if the session is not visible in a namespace
+    pub sid: Vec<u32>,
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+impl NamespacePids {
+    /// How many pid namespaces the process's own namespace is nested below the procfs mount's
+    /// namespace, 0 if it is in the same namespace
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.pid.len().saturating_sub(1)
+    }
+
+    /// The process id in the procfs mount's pid namespace
+    #[must_use]
+    pub fn host_pid(&self) -> Option<u32> {
+        self.pid.first().copied()
+    }
+
+    /// The process id in the process's own pid namespace, as seen inside a container
+    #[must_use]
+    pub fn own_pid(&self) -> Option<u32> {
+        self.pid.last().copied()
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+// Parse the namespace id fields from the contents of a `/proc/<pid>/status` file
+fn parse_namespace_pids(status: &str) -> Result<NamespacePids, String> {
+    let field = |name: &str| -> Result<Vec<u32>, String> {
+        let line = status
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(':'))
+            .ok_or_else(|| format!("Could not find the field named '{name}'"))?;
+        line.split_whitespace()
+            .map(|id| {
+                id.parse()
+                    .map_err(|_| format!("Could not parse '{id}' in field '{name}'"))
+            })
+            .collect()
+    };
+    Ok(NamespacePids {
+        pid: field("NSpid")?,
+        tgid: field("NStgid")?,
+        pgid: field("NSpgid")?,
+        sid: field("NSsid")?,
+    })
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// Get the ids of the process with the provided pid in each of the pid namespaces it is
+/// visible in, e.g. to match the pids logged inside a container to those on the host.
+///
+/// # Errors
+///
+/// An `Err` is returned if the process with PID `pid` does not exist, or its status file in
+/// the procfs file system cannot be read or lacks the fields (added in Linux 4.1)
+///
+/// # Examples
+///
+/// ```
+/// use libproc::libproc::proc_pid::namespace_pids;
+/// use std::process;
+///
+/// let pids = namespace_pids(process::id() as i32).expect("Could not get namespace pids");
+/// println!("This process is nested {} pid namespaces deep, with pid {:?} in its own",
+///          pids.depth(), pids.own_pid());
+/// ```
+pub fn namespace_pids(pid: pid_t) -> Result<NamespacePids, String> {
+    let filename = format!("/proc/{pid}/status");
+    let status = fs::read_to_string(&filename)
+        .map_err(|e| format!("Could not read /proc file '{filename}': {e}"))?;
+    parse_namespace_pids(&status).map_err(|e| format!("{e} in /proc file '{filename}'"))
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// Get how many pid namespaces the process with the provided pid is nested below the caller's,
+/// 0 if it is in the same pid namespace, e.g. 1 for a process in a container.
+///
+/// # Errors
+///
+/// An `Err` is returned if the process with PID `pid` does not exist, or its status file in
+/// the procfs file system cannot be read or lacks the namespace fields (added in Linux 4.1)
+///
+/// # Examples
+///
+/// ```
+/// use libproc::libproc::proc_pid::pid_namespace_depth;
+/// use std::process;
+///
+/// if let Ok(depth) = pid_namespace_depth(process::id() as i32) {
+///     println!("This process is nested {} pid namespaces deep", depth);
+/// }
+/// ```
+pub fn pid_namespace_depth(pid: pid_t) -> Result<usize, String> {
+    namespace_pids(pid).map(|pids| pids.depth())
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// Get the pid, in the caller's pid namespace, of the process with pid `container_pid` in the
+/// pid namespace with inode `namespace` (see [`namespace`] with [`Namespace::Pid`]).
+///
+/// Only processes whose own pid namespace is `namespace` are searched, not those in namespaces
+/// nested inside it.
+///
+/// # Errors
+///
+/// An `Err` is returned if the processes on the system cannot be listed, or no process with
+/// pid `container_pid` that the caller can inspect is found in `namespace`
+///
+/// # Examples
+///
+/// ```
+/// use libproc::libproc::proc_pid::{host_pid_for, namespace, Namespace};
+///
+/// // Find the host pid of the init process of the container that process 4321 is in
+/// if let Ok(container) = namespace(4321, Namespace::Pid) {
+///     if let Ok(pid) = host_pid_for(1, container) {
+///         println!("The container's init process is {} on the host", pid);
+///     }
+/// }
+/// ```
+pub fn host_pid_for(container_pid: u32, namespace: u64) -> Result<u32, String> {
+    for pid in processes::pids_by_type(processes::ProcFilter::All).map_err(|e| e.to_string())? {
+        let Ok(signed_pid) = i32::try_from(pid) else {
+            continue;
+        };
+        // Processes may exit while being scanned
+        if self::namespace(signed_pid, Namespace::Pid).ok() != Some(namespace) {
+            continue;
+        }
+        if let Ok(pids) = namespace_pids(signed_pid) {
+            if pids.own_pid() == Some(container_pid) {
+                return Ok(pid);
+            }
+        }
+    }
+    Err(format!(
+        "Could not find process {container_pid} in pid namespace {namespace}"
+    ))
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// Get the pid in its own pid namespace of the process with pid `host_pid` in the caller's
+/// namespace, if its pid namespace has the inode `namespace`. This is the reverse of
+/// [`host_pid_for`].
+///
+/// # Errors
+///
+/// An `Err` is returned if the process with PID `host_pid` does not exist, the caller cannot
+/// inspect it, or it is not in `namespace`
+///
+/// # Examples
+///
+/// ```
+/// use libproc::libproc::proc_pid::{container_pid_for, namespace, Namespace};
+/// use std::process;
+///
+/// let pid = process::id();
+/// let own = namespace(pid as i32, Namespace::Pid).expect("Could not get namespace");
+/// let container_pid = container_pid_for(pid, own).expect("Could not translate pid");
+/// println!("Process {} has pid {} in its own namespace", pid, container_pid);
+/// ```
+pub fn container_pid_for(host_pid: u32, namespace: u64) -> Result<u32, String> {
+    let signed_pid = i32::try_from(host_pid).map_err(|_| format!("Invalid pid {host_pid}"))?;
+    let own = self::namespace(signed_pid, Namespace::Pid)?;
+    if own != namespace {
+        return Err(format!(
+            "Process {host_pid} is in pid namespace {own}, not {namespace}"
+        ));
+    }
+    namespace_pids(signed_pid)?
+        .own_pid()
+        .ok_or_else(|| format!("No pids found for process {host_pid}"))
+}
+
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+/// Get the sockets open in the process with the provided pid, and the file descriptors they
+/// are open on, with their addresses as `std::net` types.
+///
+/// Sockets are found in the socket tables of the process's network namespace under
+/// `/proc/<pid>/net`. Sockets of kinds that are not listed there (e.g. bluetooth) are omitted.
+///
+/// # Errors
+///
+/// An `Err` is returned if the process with PID `pid` does not exist, the caller does not have
+/// permission to read its file descriptors, or its socket tables cannot be parsed
+///
+/// # Examples
+///
+/// ```
+/// use libproc::libproc::proc_pid::sockets;
+/// use std::process;
+///
+/// for open in sockets(process::id() as i32).expect("Could not get sockets") {
+///     println!("fd {}: {:?} local {:?} remote {:?}", open.fd, open.socket.state(),
+///              open.socket.local_addr(), open.socket.remote_addr());
+/// }
+/// ```
+pub fn sockets(pid: pid_t) -> Result<Vec<OpenSocket>, String> {
+    let inodes = net_info::socket_fds(pid)?;
+    if inodes.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let table = net_info::socket_table(Path::new(&format!("/proc/{pid}/net")))?;
+    Ok(inodes
+        .into_iter()
+        .filter_map(|(fd, inode)| {
+            // A socket may be open on more than one descriptor
+            let socket = table.get(&inode)?.clone();
+            Some(OpenSocket { fd, socket })
+        })
+        .collect())
+}
+
+#[cfg(target_os = "macos")]
+/// Get the sockets open in the process with the provided pid, and the file descriptors they
+/// are open on, with their addresses as `std::net` types.
+///
+/// This is a safe alternative to using [`pidfdinfo`] with
+/// [`SocketFDInfo`][crate::libproc::net_info::SocketFDInfo] on each file descriptor. Sockets
+/// of kinds not represented by [`Socket`][crate::libproc::net_info::Socket] are omitted.
+///
+/// # Errors
+///
+/// An `Err` is returned if the process with PID `pid` does not exist or the caller does not
+/// have permission to get information about its file descriptors
+///
+/// # Examples
+///
+/// ```
+/// use libproc::libproc::proc_pid::sockets;
+/// use std::process;
+///
+/// for open in sockets(process::id() as i32).expect("Could not get sockets") {
+///     println!("fd {}: {:?} local {:?} remote {:?}", open.fd, open.socket.state(),
+///              open.socket.local_addr(), open.socket.remote_addr());
+/// }
+/// ```
+pub fn sockets(pid: pid_t) -> Result<Vec<OpenSocket>, String> {
+    let info = pidinfo::<BSDInfo>(pid, 0)?;
+    let fds = listpidinfo::<ListFDs>(pid, info.pbi_nfiles as usize)?;
+
+    Ok(fds
+        .into_iter()
+        .filter(|fd| matches!(ProcFDType::from(fd.proc_fdtype), ProcFDType::Socket))
+        .filter_map(|fd| {
+            // The descriptor may have been closed since it was listed
+            let info = pidfdinfo::<SocketFDInfo>(pid, fd.proc_fd).ok()?;
+            Socket::from_socket_info(&info.psi).map(|socket| OpenSocket {
+                fd: fd.proc_fd,
+                socket,
+            })
+        })
+        .collect())
+}
+
 /// Determine if the current user ID of this process is root
 ///
 /// # Examples
@@ -700,8 +1459,13 @@ mod test {
 
     use super::am_root;
     #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
-    use super::pidcwd;
-    use super::{cwdself, name, pidpath};
+    use super::{
+        cgroups, container_pid_for, file_progress, host_pid_for, locks, mounts, namespace,
+        namespace_pids, namespaces, oom_score, oom_score_adj, open_files, parse_namespace_link,
+        parse_namespace_pids, pid_namespace_depth, pidcwd, set_oom_score_adj, translate_path,
+        Namespace,
+    };
+    use super::{cwdself, name, pidpath, sockets};
     #[cfg(target_os = "macos")]
     use super::{libversion, listpidinfo, pidinfo, ListThreads};
     #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
@@ -891,6 +1655,243 @@ mod test {
         );
     }
 
+    #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+    #[test]
+    fn open_files_deleted_test() {
+        use std::io::Write;
+
+        let mut file = tempfile::tempfile().expect("Could not create temp file");
+        file.write_all(b"libproc")
+            .expect("Could not write to temp file");
+        let files = open_files(process::id() as i32).expect("open_files() failed");
+        assert!(files
+            .iter()
+            .any(|f| f.deleted && f.offset == 7 && f.path.is_absolute()));
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+    #[test]
+    fn file_progress_test() {
+        use std::io::{Seek, SeekFrom, Write};
+
+        let mut file = tempfile::tempfile().expect("Could not create temp file");
+        file.write_all(&[0; 1000])
+            .expect("Could not write to temp file");
+        file.seek(SeekFrom::Start(250))
+            .expect("Could not seek in temp file");
+        let progress = file_progress(process::id() as i32).expect("file_progress() failed");
+        assert!(progress
+            .iter()
+            .any(|p| p.size == 1000 && p.position == 250 && (p.percent() - 25.0).abs() < 0.01));
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+    #[test]
+    fn locks_test() {
+        use crate::libproc::lock_info::{LockAccess, LockKind};
+        use std::os::unix::io::AsRawFd;
+
+        let dir = tempfile::tempdir().expect("Could not create temp dir");
+        let path = dir.path().join("locked");
+        let file = std::fs::File::create(&path).expect("Could not create temp file");
+        assert_eq!(
+            unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) },
+            0,
+            "flock() failed"
+        );
+
+        let held = locks(process::id() as i32).expect("locks() failed");
+        let lock = held
+            .iter()
+            .find(|lock| lock.path.as_deref() == Some(path.as_path()))
+            .expect("Lock not found");
+        assert_eq!(lock.kind, LockKind::Flock);
+        assert_eq!(lock.access, LockAccess::Write);
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+    #[test]
+    fn mounts_test() {
+        let mounts = mounts(process::id() as i32).expect("mounts() failed");
+        assert!(mounts
+            .iter()
+            .any(|m| m.mount_point == std::path::Path::new("/")));
+        assert!(mounts.iter().any(|m| m.fs_type == "proc"));
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+    #[test]
+    fn cgroups_test() {
+        let own = cgroups(process::id() as i32).expect("cgroups() failed");
+        assert!(!own.is_empty());
+        assert!(own.iter().all(|cgroup| cgroup.path.is_absolute()));
+        assert!(cgroups(-1).is_err());
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+    #[test]
+    fn translate_path_test() {
+        use std::path::Path;
+
+        let pid = process::id() as i32;
+        let translated = translate_path(pid, Path::new("/proc/self/status"));
+        assert_eq!(
+            translated,
+            Path::new(&format!("/proc/{pid}/root/proc/self/status"))
+        );
+        assert!(translated.exists());
+        assert_eq!(
+            translate_path(pid, Path::new("Cargo.toml")),
+            Path::new(&format!("/proc/{pid}/cwd/Cargo.toml"))
+        );
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+    #[test]
+    fn oom_score_test() {
+        assert!(oom_score(process::id() as i32).expect("oom_score() failed") <= 2000);
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+    #[test]
+    fn set_oom_score_adj_test() {
+        // Adjust a child rather than the test process, which would then be more likely to be
+        // killed for the rest of the run
+        let mut child = process::Command::new("sleep")
+            .arg("10")
+            .spawn()
+            .expect("Could not spawn child");
+        let pid = child.id() as i32;
+        let original = oom_score_adj(pid).expect("oom_score_adj() failed");
+        // Raising the adjustment never needs privileges
+        let raised = (original + 1).min(1000);
+        let result = set_oom_score_adj(pid, raised).and_then(|()| oom_score_adj(pid));
+        child.kill().expect("Could not kill child");
+        child.wait().expect("Could not wait for child");
+        assert_eq!(result, Ok(raised));
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+    #[test]
+    fn parse_namespace_link_test() {
+        use std::path::Path;
+
+        assert_eq!(
+            parse_namespace_link(Path::new("net:[4026531992]"), Namespace::Net),
+            Some(4_026_531_992)
+        );
+        assert_eq!(
+            parse_namespace_link(Path::new("pid:[4026531836]"), Namespace::PidForChildren),
+            Some(4_026_531_836)
+        );
+        assert_eq!(
+            parse_namespace_link(Path::new("mnt:[4026531992]"), Namespace::Net),
+            None
+        );
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+    #[test]
+    fn namespaces_test() {
+        let pid = process::id() as i32;
+        let all = namespaces(pid).expect("namespaces() failed");
+        for ns in [
+            Namespace::Mnt,
+            Namespace::Net,
+            Namespace::Pid,
+            Namespace::User,
+        ] {
+            assert_eq!(
+                all.get(&ns).copied(),
+                Some(namespace(pid, ns).expect("namespace() failed"))
+            );
+        }
+        // A process that has not unshared its pid namespace creates children in its own
+        if let Some(for_children) = all.get(&Namespace::PidForChildren) {
+            assert_eq!(Some(for_children), all.get(&Namespace::Pid));
+        }
+        assert!(namespaces(-1).is_err());
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+    #[test]
+    fn parse_namespace_pids_test() {
+        let status = "\
+Name:\tnginx
+Tgid:\t48213
+Pid:\t48213
+PPid:\t48190
+NStgid:\t48213\t7
+NSpid:\t48213\t7
+NSpgid:\t48190\t1
+NSsid:\t48190\t1
+VmPeak:\t   10800 kB
+";
+        let pids = parse_namespace_pids(status).expect("Could not parse status");
+        assert_eq!(pids.pid, vec![48213, 7]);
+        assert_eq!(pids.tgid, vec![48213, 7]);
+        assert_eq!(pids.pgid, vec![48190, 1]);
+        assert_eq!(pids.sid, vec![48190, 1]);
+        assert_eq!(pids.depth(), 1);
+        assert_eq!(pids.host_pid(), Some(48213));
+        assert_eq!(pids.own_pid(), Some(7));
+
+        assert!(parse_namespace_pids("Name:\tinit\nPid:\t1\n").is_err());
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+    #[test]
+    fn namespace_pids_test() {
+        let pid = process::id();
+        let pids = namespace_pids(pid as i32).expect("namespace_pids() failed");
+        assert_eq!(pids.host_pid(), Some(pid));
+        assert_eq!(pids.pid.len(), pids.depth() + 1);
+        assert_eq!(pid_namespace_depth(pid as i32), Ok(pids.depth()));
+
+        let own = namespace(pid as i32, Namespace::Pid).expect("namespace() failed");
+        let container_pid = container_pid_for(pid, own).expect("container_pid_for() failed");
+        assert_eq!(Some(container_pid), pids.own_pid());
+        assert_eq!(host_pid_for(container_pid, own), Ok(pid));
+        assert!(container_pid_for(pid, own + 1).is_err());
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+    #[test]
+    fn set_oom_score_adj_out_of_range_test() {
+        assert!(set_oom_score_adj(process::id() as i32, 1001).is_err());
+        assert!(set_oom_score_adj(process::id() as i32, -1001).is_err());
+    }
+
+    #[test]
+    fn sockets_test() {
+        use crate::libproc::net_info::{Socket, TcpSIState};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Could not bind listener");
+        let local = listener.local_addr().expect("Could not get local address");
+        let open = sockets(process::id() as i32).expect("sockets() failed");
+        let found = open
+            .iter()
+            .find(|open| open.socket.local_addr() == Some(local))
+            .expect("Listening socket not found");
+        assert!(matches!(found.socket, Socket::Tcp(_)));
+        assert_eq!(found.socket.state(), Some(TcpSIState::Listen));
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+    #[test]
+    fn unix_sockets_test() {
+        use crate::libproc::net_info::Socket;
+        use std::os::unix::net::UnixListener;
+
+        let dir = tempfile::tempdir().expect("Could not create temporary directory");
+        let path = dir.path().join("test.sock");
+        let _listener = UnixListener::bind(&path).expect("Could not bind listener");
+        let open = sockets(process::id() as i32).expect("sockets() failed");
+        assert!(open.iter().any(|open| matches!(&open.socket,
+            Socket::Unix(unix) if unix.listening && unix.path.as_deref() == path.to_str())));
+    }
+
     #[test]
     fn am_root_test() {
         if am_root() {
@@ -0,0 +1,404 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::io;
+use std::mem::size_of;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::os::unix::io::{AsRawFd, FromRawFd, OwnedFd};
+
+use libc::{
+    c_void, nlmsghdr, sockaddr_nl, AF_INET, AF_INET6, AF_NETLINK, AF_UNIX, NETLINK_SOCK_DIAG,
+    NLMSG_DONE, NLMSG_ERROR, NLM_F_DUMP, NLM_F_REQUEST, SOCK_CLOEXEC, SOCK_DGRAM,
+};
+
+use crate::libproc::net_info::{InetSocket, InetSocketInfo, TcpInfo, TcpSIState};
+
+// Netlink message type of a `sock_diag` request for sockets of one address family
+const SOCK_DIAG_BY_FAMILY: u16 = 20;
+
+// Request the inode of the peer of each Unix socket, and the attribute it is returned in
+const UDIAG_SHOW_PEER: u32 = 0x4;
+const UNIX_DIAG_PEER: u16 = 2;
+
+// Request `struct tcp_info` for each TCP socket, and the attribute it is returned in
+const INET_DIAG_INFO: u16 = 2;
+const INET_DIAG_EXT_INFO: u8 = 1 << (INET_DIAG_INFO - 1);
+
+// Size of `struct inet_diag_msg`, which precedes the attributes of each IP socket returned
+const INET_DIAG_MSG_LEN: usize = 72;
+
+// Size of `struct unix_diag_msg`, which precedes the attributes of each Unix socket returned
+const UNIX_DIAG_MSG_LEN: usize = 16;
+
+// Size of the buffer used to receive netlink messages, as recommended by netlink(7)
+const RECV_BUFFER_LEN: usize = 32 * 1024;
+
+// Netlink messages and attributes are aligned to 4 bytes
+fn align(len: usize) -> usize {
+    len.div_ceil(4) * 4
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_ne_bytes(
+        <[u8; 2]>::try_from(bytes.get(offset..offset + 2)?).ok()?,
+    ))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_ne_bytes(
+        <[u8; 4]>::try_from(bytes.get(offset..offset + 4)?).ok()?,
+    ))
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_ne_bytes(
+        <[u8; 8]>::try_from(bytes.get(offset..offset + 8)?).ok()?,
+    ))
+}
+
+/// Iterate over the `(type, payload)` of the netlink attributes (`struct rtattr`) in `bytes`
+pub(crate) fn attributes(bytes: &[u8]) -> impl Iterator<Item = (u16, &[u8])> {
+    let mut offset = 0;
+    std::iter::from_fn(move || {
+        let len = usize::from(read_u16(bytes, offset)?);
+        let kind = read_u16(bytes, offset + 2)?;
+        let payload = bytes.get(offset + 4..offset + len)?;
+        offset += align(len.max(4));
+        Some((kind, payload))
+    })
+}
+
+fn netlink_socket() -> io::Result<OwnedFd> {
+    let fd = unsafe { libc::socket(AF_NETLINK, SOCK_DGRAM | SOCK_CLOEXEC, NETLINK_SOCK_DIAG) };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // The descriptor was just created and is owned by nothing else
+    Ok(unsafe { OwnedFd::from_raw_fd(fd) })
+}
+
+/// Send a `SOCK_DIAG_BY_FAMILY` dump `request` to the kernel and return the payload of each
+/// message of the reply
+///
+/// # Errors
+///
+/// Will return `Err` if a netlink socket cannot be created, e.g. in a sandbox without network
+/// access, or the kernel does not support the request
+pub(crate) fn dump(request: &[u8]) -> Result<Vec<Vec<u8>>, String> {
+    let error = |e: io::Error| format!("sock_diag request failed: {e}");
+    let socket = netlink_socket().map_err(error)?;
+
+    let header_len = size_of::<nlmsghdr>();
+    let message_len = u32::try_from(header_len + request.len()).map_err(|e| e.to_string())?;
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let flags = (NLM_F_REQUEST | NLM_F_DUMP) as u16;
+
+    // struct nlmsghdr: length, type, flags, sequence number and port id, then the request
+    let mut message = Vec::with_capacity(header_len + request.len());
+    message.extend_from_slice(&message_len.to_ne_bytes());
+    message.extend_from_slice(&SOCK_DIAG_BY_FAMILY.to_ne_bytes());
+    message.extend_from_slice(&flags.to_ne_bytes());
+    message.extend_from_slice(&1u32.to_ne_bytes());
+    message.extend_from_slice(&0u32.to_ne_bytes());
+    message.extend_from_slice(request);
+
+    // Address the kernel, which has port id 0
+    let mut kernel: sockaddr_nl = unsafe { std::mem::zeroed() };
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    {
+        kernel.nl_family = AF_NETLINK as u16;
+    }
+    #[allow(clippy::cast_possible_truncation)]
+    let sent = unsafe {
+        libc::sendto(
+            socket.as_raw_fd(),
+            message.as_ptr().cast::<c_void>(),
+            message.len(),
+            0,
+            std::ptr::addr_of!(kernel).cast(),
+            size_of::<sockaddr_nl>() as u32,
+        )
+    };
+    if sent < 0 {
+        return Err(error(io::Error::last_os_error()));
+    }
+
+    let mut payloads = Vec::new();
+    let mut buffer = vec![0u8; RECV_BUFFER_LEN];
+    loop {
+        let received = unsafe {
+            libc::recv(
+                socket.as_raw_fd(),
+                buffer.as_mut_ptr().cast::<c_void>(),
+                buffer.len(),
+                0,
+            )
+        };
+        let received = usize::try_from(received).map_err(|_| error(io::Error::last_os_error()))?;
+        if received == 0 {
+            return Ok(payloads);
+        }
+
+        let mut offset = 0;
+        while offset + header_len <= received {
+            let len = read_u32(&buffer, offset).map_or(0, |len| len as usize);
+            let kind = read_u16(&buffer, offset + 4).unwrap_or_default();
+            if len < header_len || offset + len > received {
+                return Err("sock_diag reply is truncated".into());
+            }
+            let payload = &buffer[offset + header_len..offset + len];
+            match i32::from(kind) {
+                NLMSG_DONE => return Ok(payloads),
+                NLMSG_ERROR => {
+                    // The payload starts with the negated errno, 0 being an acknowledgement
+                    #[allow(clippy::cast_possible_wrap)]
+                    let errno = read_u32(payload, 0).map_or(0, |e| e as i32);
+                    if errno != 0 {
+                        return Err(error(io::Error::from_raw_os_error(-errno)));
+                    }
+                }
+                _ => payloads.push(payload.to_vec()),
+            }
+            offset += align(len);
+        }
+    }
+}
+
+/// Get the inode of the peer of each connected Unix socket in the caller's network namespace,
+/// keyed by the inode of the socket, using the `UNIX_DIAG_PEER` attribute of `sock_diag`
+///
+/// # Errors
+///
+/// Will return `Err` if the request fails, e.g. when the kernel was built without
+/// `CONFIG_UNIX_DIAG`
+pub(crate) fn unix_peers() -> Result<HashMap<u64, u64>, String> {
+    // struct unix_diag_req, selecting sockets in all states
+    let mut request = Vec::with_capacity(24);
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    request.push(AF_UNIX as u8);
+    request.extend_from_slice(&[0, 0, 0]);
+    request.extend_from_slice(&u32::MAX.to_ne_bytes());
+    request.extend_from_slice(&0u32.to_ne_bytes());
+    request.extend_from_slice(&UDIAG_SHOW_PEER.to_ne_bytes());
+    request.extend_from_slice(&[0xff; 8]);
+
+    let mut peers = HashMap::new();
+    for payload in dump(&request)? {
+        let Some(inode) = read_u32(&payload, 4) else {
+            continue;
+        };
+        let peer = attributes(payload.get(UNIX_DIAG_MSG_LEN..).unwrap_or_default())
+            .find(|(kind, _)| *kind == UNIX_DIAG_PEER)
+            .and_then(|(_, value)| read_u32(value, 0));
+        if let Some(peer) = peer.filter(|peer| *peer != 0) {
+            peers.insert(u64::from(inode), u64::from(peer));
+        }
+    }
+    Ok(peers)
+}
+
+/// Parse a `struct tcp_info`. Fields added in later kernel versions are `None` if the running
+/// kernel does not report them
+pub(crate) fn parse_tcp_info(bytes: &[u8]) -> Option<TcpInfo> {
+    Some(TcpInfo {
+        state: TcpSIState::from_linux(*bytes.first()?),
+        retransmits: *bytes.get(2)?,
+        rto: read_u32(bytes, 8)?,
+        snd_mss: read_u32(bytes, 16)?,
+        unacked: read_u32(bytes, 24)?,
+        lost: read_u32(bytes, 32)?,
+        rtt: read_u32(bytes, 68)?,
+        rttvar: read_u32(bytes, 72)?,
+        snd_ssthresh: read_u32(bytes, 76)?,
+        snd_cwnd: read_u32(bytes, 80)?,
+        total_retrans: read_u32(bytes, 100)?,
+        bytes_acked: read_u64(bytes, 120),
+        bytes_received: read_u64(bytes, 128),
+        min_rtt: read_u32(bytes, 148),
+        delivery_rate: read_u64(bytes, 160),
+    })
+}
+
+// Addresses in `struct inet_diag_sockid` are in network byte order
+fn parse_addr(family: u8, addr: &[u8], port: &[u8]) -> Option<SocketAddr> {
+    let port = u16::from_be_bytes(<[u8; 2]>::try_from(port).ok()?);
+    let ip = if i32::from(family) == AF_INET6 {
+        IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(addr).ok()?))
+    } else {
+        IpAddr::V4(Ipv4Addr::from(<[u8; 4]>::try_from(addr.get(..4)?).ok()?))
+    };
+    Some(SocketAddr::new(ip, port))
+}
+
+/// Parse a `struct inet_diag_msg` and its attributes
+pub(crate) fn parse_inet_diag_msg(msg: &[u8]) -> Option<InetSocketInfo> {
+    let family = *msg.first()?;
+    let state = TcpSIState::from_linux(*msg.get(1)?);
+    let local = parse_addr(family, msg.get(8..24)?, msg.get(4..6)?)?;
+    let remote = parse_addr(family, msg.get(24..40)?, msg.get(6..8)?)?;
+    let tcp_info = attributes(msg.get(INET_DIAG_MSG_LEN..).unwrap_or_default())
+        .find(|(kind, _)| *kind == INET_DIAG_INFO)
+        .and_then(|(_, value)| parse_tcp_info(value));
+
+    Some(InetSocketInfo {
+        socket: InetSocket {
+            local,
+            remote,
+            state,
+            rx_queue: read_u32(msg, 56)?,
+            tx_queue: read_u32(msg, 60)?,
+            uid: read_u32(msg, 64)?,
+            inode: u64::from(read_u32(msg, 68)?),
+        },
+        tcp_info,
+    })
+}
+
+/// Get the IPv4 and IPv6 sockets of IP `protocol` (e.g. `IPPROTO_TCP`) in the caller's network
+/// namespace using `INET_DIAG`, with `struct tcp_info` for TCP sockets
+///
+/// # Errors
+///
+/// Will return `Err` if the request fails, e.g. when the kernel was built without
+/// `CONFIG_INET_DIAG` or the protocol is not supported
+pub(crate) fn inet_sockets(protocol: u8) -> Result<Vec<InetSocketInfo>, String> {
+    let mut sockets = Vec::new();
+    for family in [AF_INET, AF_INET6] {
+        // struct inet_diag_req_v2, selecting sockets in all states
+        let mut request = Vec::with_capacity(56);
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        request.push(family as u8);
+        request.push(protocol);
+        request.push(INET_DIAG_EXT_INFO);
+        request.push(0);
+        request.extend_from_slice(&u32::MAX.to_ne_bytes());
+        // struct inet_diag_sockid, matching any socket
+        request.extend_from_slice(&[0; 40]);
+        request.extend_from_slice(&[0xff; 8]);
+
+        for payload in dump(&request)? {
+            sockets.push(
+                parse_inet_diag_msg(&payload)
+                    .ok_or_else(|| String::from("Could not parse inet_diag_msg"))?,
+            );
+        }
+    }
+    Ok(sockets)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{attributes, parse_inet_diag_msg, parse_tcp_info, unix_peers};
+    use crate::libproc::net_info::TcpSIState;
+    use std::convert::TryFrom;
+    use std::os::unix::fs::MetadataExt;
+    use std::os::unix::net::UnixStream;
+
+    #[test]
+    fn parse_attributes() {
+        // A 4 byte attribute of type 2, then a 5 byte attribute of type 3 padded to 8 bytes
+        let bytes = [8, 0, 2, 0, 1, 2, 3, 4, 5, 0, 3, 0, 9, 0, 0, 0];
+        let attributes: Vec<_> = attributes(&bytes).collect();
+        assert_eq!(attributes, vec![(2, &[1, 2, 3, 4][..]), (3, &[9][..])]);
+    }
+
+    #[test]
+    fn parse_truncated_attributes() {
+        assert_eq!(attributes(&[8, 0, 2, 0, 1]).count(), 0);
+    }
+
+    #[test]
+    fn unix_peers_of_socket_pair() {
+        let (a, b) = UnixStream::pair().expect("Could not create socket pair");
+        let inode = |stream: &UnixStream| {
+            let fd = std::os::unix::io::AsRawFd::as_raw_fd(stream);
+            std::fs::metadata(format!("/proc/self/fd/{fd}"))
+                .expect("Could not stat socket")
+                .ino()
+        };
+        match unix_peers() {
+            Ok(peers) => {
+                assert_eq!(peers.get(&inode(&a)), Some(&inode(&b)));
+                assert_eq!(peers.get(&inode(&b)), Some(&inode(&a)));
+            }
+            Err(e) => println!("test skipped as sock_diag is not available: {e}"),
+        }
+    }
+
+    // A struct tcp_info as reported by a Linux 4.6 kernel, without `delivery_rate`
+    fn tcp_info_fixture() -> Vec<u8> {
+        let mut info = vec![0u8; 160];
+        info[0] = 1; // ESTABLISHED
+        info[2] = 3;
+        info[68..72].copy_from_slice(&250u32.to_ne_bytes());
+        info[72..76].copy_from_slice(&125u32.to_ne_bytes());
+        info[80..84].copy_from_slice(&10u32.to_ne_bytes());
+        info[100..104].copy_from_slice(&7u32.to_ne_bytes());
+        info[120..128].copy_from_slice(&1_000_000u64.to_ne_bytes());
+        info[128..136].copy_from_slice(&2_000u64.to_ne_bytes());
+        info
+    }
+
+    #[test]
+    fn parse_tcp_info_fields() {
+        let info = parse_tcp_info(&tcp_info_fixture()).expect("Could not parse tcp_info");
+        assert_eq!(info.state, TcpSIState::Established);
+        assert_eq!(info.retransmits, 3);
+        assert_eq!(info.rtt, 250);
+        assert_eq!(info.rttvar, 125);
+        assert_eq!(info.snd_cwnd, 10);
+        assert_eq!(info.total_retrans, 7);
+        assert_eq!(info.bytes_acked, Some(1_000_000));
+        assert_eq!(info.bytes_received, Some(2_000));
+        assert_eq!(info.min_rtt, Some(0));
+        assert_eq!(info.delivery_rate, None);
+    }
+
+    #[test]
+    fn parse_short_tcp_info() {
+        assert!(parse_tcp_info(&[1; 64]).is_none());
+    }
+
+    #[test]
+    fn parse_inet_diag_message() {
+        let mut msg = vec![0u8; 72];
+        msg[0] = 2; // AF_INET
+        msg[1] = 1; // ESTABLISHED
+        msg[4..6].copy_from_slice(&8080u16.to_be_bytes());
+        msg[6..8].copy_from_slice(&40000u16.to_be_bytes());
+        msg[8..12].copy_from_slice(&[127, 0, 0, 1]);
+        msg[24..28].copy_from_slice(&[10, 0, 0, 2]);
+        msg[56..60].copy_from_slice(&5u32.to_ne_bytes());
+        msg[64..68].copy_from_slice(&1000u32.to_ne_bytes());
+        msg[68..72].copy_from_slice(&4242u32.to_ne_bytes());
+        let info = tcp_info_fixture();
+        msg.extend_from_slice(
+            &u16::try_from(info.len() + 4)
+                .expect("Too long")
+                .to_ne_bytes(),
+        );
+        msg.extend_from_slice(&2u16.to_ne_bytes());
+        msg.extend_from_slice(&info);
+
+        let parsed = parse_inet_diag_msg(&msg).expect("Could not parse inet_diag_msg");
+        assert_eq!(parsed.socket.local.to_string(), "127.0.0.1:8080");
+        assert_eq!(parsed.socket.remote.to_string(), "10.0.0.2:40000");
+        assert_eq!(parsed.socket.state, TcpSIState::Established);
+        assert_eq!(parsed.socket.rx_queue, 5);
+        assert_eq!(parsed.socket.uid, 1000);
+        assert_eq!(parsed.socket.inode, 4242);
+        assert_eq!(parsed.tcp_info.map(|info| info.rtt), Some(250));
+    }
+
+    #[test]
+    fn parse_inet6_diag_message() {
+        let mut msg = vec![0u8; 72];
+        msg[0] = 10; // AF_INET6
+        msg[1] = 10; // LISTEN
+        msg[4..6].copy_from_slice(&22u16.to_be_bytes());
+        msg[23] = 1;
+        let parsed = parse_inet_diag_msg(&msg).expect("Could not parse inet_diag_msg");
+        assert_eq!(parsed.socket.local.to_string(), "[::1]:22");
+        assert_eq!(parsed.socket.state, TcpSIState::Listen);
+        assert_eq!(parsed.tcp_info, None);
+    }
+}
@@ -1,5 +1,7 @@
 use std::{fs, io, path};
 
+use crate::libproc::cgroup;
+use crate::libproc::proc_pid::{self, Namespace};
 use crate::processes::ProcFilter;
 
 const FIRST_FIELD: isize = 2;
@@ -52,10 +54,34 @@ fn file_owner_uid(path: &path::Path) -> Option<u32> {
     fs::metadata(path).map(|md| md.uid()).ok()
 }
 
-/// Reads process information from /proc/pid/{,stat} to enumerate PIDs matching the filter
+/// Get the inode of a namespace of the process with a path starting with /proc/pid, as an
+/// option. Errors are ignored, it is assumed the process went away.
+fn namespace_inode(proc_path: &path::Path, namespace: Namespace) -> Option<u64> {
+    proc_pid::read_namespace(&proc_path.join("ns").join(namespace.name()), namespace).ok()
+}
+
+/// Return true if the process with a path starting with /proc/pid is in a cgroup at or below
+/// `prefix` in any hierarchy. Errors are ignored, it is assumed the process went away.
+fn in_cgroup(proc_path: &path::Path, prefix: &path::Path) -> bool {
+    fs::File::open(proc_path.join("cgroup"))
+        .ok()
+        .and_then(|f| cgroup::parse_proc_cgroup(io::BufReader::new(f)).ok())
+        .is_some_and(|cgroups| cgroups.iter().any(|c| c.path.starts_with(prefix)))
+}
+
+/// Reads process information from /proc/pid/{,stat,ns,cgroup} to enumerate PIDs matching the filter
 pub fn listpids(proc_types: ProcFilter) -> io::Result<Vec<u32>> {
     let mut pids = Vec::<u32>::new();
 
+    // The namespace processes are compared to is read once, and must exist
+    let same_namespace = match proc_types {
+        ProcFilter::ByNamespace { namespace, pid } => {
+            let link = path::Path::new(&format!("/proc/{pid}/ns")).join(namespace.name());
+            Some(proc_pid::read_namespace(&link, namespace).map_err(io::Error::other)?)
+        }
+        _ => None,
+    };
+
     let proc_dir = fs::read_dir("/proc")?;
 
     for entry in proc_dir {
@@ -79,6 +105,10 @@ pub fn listpids(proc_types: ProcFilter) -> io::Result<Vec<u32>> {
                         ProcFilter::ByParentProcess { ppid } => {
                             proc_stat_field(&path, ProcStatField::Ppid) == Some(ppid)
                         }
+                        ProcFilter::ByNamespace { namespace, .. } => {
+                            namespace_inode(&path, namespace) == same_namespace
+                        }
+                        ProcFilter::ByCgroup { prefix } => in_cgroup(&path, prefix),
                     };
                     if matches {
                         pids.push(pid);
@@ -354,4 +384,51 @@ mod test {
         }
         panic!("Test failed");
     }
+
+    #[test]
+    fn test_listpids_namespace() {
+        let own_pid = std::process::id();
+        let filter = ProcFilter::ByNamespace {
+            namespace: Namespace::Net,
+            pid: own_pid,
+        };
+        let pids = listpids(filter).expect("listpids() failed");
+        assert!(pids.contains(&own_pid));
+
+        let own = proc_pid::namespace(own_pid as i32, Namespace::Net).expect("namespace() failed");
+        for pid in pids {
+            // Processes may exit while being checked
+            if let Ok(namespace) = proc_pid::namespace(pid as i32, Namespace::Net) {
+                assert_eq!(namespace, own);
+            }
+        }
+
+        let missing = ProcFilter::ByNamespace {
+            namespace: Namespace::Net,
+            pid: u32::MAX,
+        };
+        assert!(listpids(missing).is_err());
+    }
+
+    #[test]
+    fn test_in_cgroup() -> Result<(), Error> {
+        let tempdir = tempfile::tempdir()?;
+        let path = tempdir.path();
+        fs::write(
+            path.join("cgroup"),
+            "4:memory:/docker/3f2a\n0::/system.slice/nginx.service\n",
+        )?;
+
+        assert!(in_cgroup(path, path::Path::new("/system.slice")));
+        assert!(in_cgroup(
+            path,
+            path::Path::new("/system.slice/nginx.service")
+        ));
+        assert!(in_cgroup(path, path::Path::new("/docker")));
+        assert!(!in_cgroup(path, path::Path::new("/system.slice/nginx")));
+        assert!(!in_cgroup(path, path::Path::new("/user.slice")));
+        assert!(!in_cgroup(&path.join("missing"), path::Path::new("/")));
+
+        Ok(())
+    }
 }
@@ -0,0 +1,270 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader};
+use std::os::unix::fs::FileExt;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use crate::libproc::helpers::parse_memory_string;
+use crate::libproc::page_info::{memory_regions, page_size, region_pages, MemoryRegion};
+
+const IDLE_PAGE_BITMAP: &str = "/sys/kernel/mm/page_idle/bitmap";
+
+// Writing this value to `/proc/<pid>/clear_refs` clears the referenced bits of all pages
+const CLEAR_ALL_REFS: &str = "1";
+
+/// The method used to detect which pages a process touched during the sampling interval
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WorkingSetMethod {
+    /// Clear the referenced bits via `/proc/<pid>/clear_refs` and then sum the `Referenced`
+    /// values of each region in `/proc/<pid>/smaps`
+    Referenced,
+    /// Use idle page tracking via `/sys/kernel/mm/page_idle/bitmap`. This does not disturb the
+    /// page reclaim of the target process, but requires a kernel built with
+    /// `CONFIG_IDLE_PAGE_TRACKING` and root privileges to read page frame numbers.
+    IdlePage,
+}
+
+/// The working set size of one region of a process's virtual memory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegionWorkingSet {
+    /// The memory region
+    pub region: MemoryRegion,
+    /// Number of bytes of the region referenced during the sampling interval
+    pub bytes: u64,
+}
+
+/// The estimated working set size of a process
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkingSetSize {
+    /// Method used to make the estimate
+    pub method: WorkingSetMethod,
+    /// Interval over which the referenced pages were sampled
+    pub interval: Duration,
+    /// Working set size of each region of memory
+    pub regions: Vec<RegionWorkingSet>,
+    /// Total working set size in bytes
+    pub total: u64,
+}
+
+impl WorkingSetSize {
+    fn new(method: WorkingSetMethod, interval: Duration, regions: Vec<RegionWorkingSet>) -> Self {
+        let total = regions.iter().map(|r| r.bytes).sum();
+        WorkingSetSize {
+            method,
+            interval,
+            regions,
+            total,
+        }
+    }
+}
+
+/// Return true if idle page tracking is available on this system
+#[must_use]
+pub fn idle_page_tracking_available() -> bool {
+    Path::new(IDLE_PAGE_BITMAP).exists()
+}
+
+/// Estimate the working set size (WSS) of the process with `pid`: the amount of memory it
+/// actually touches during `interval`, per region and in total.
+///
+/// # Warning
+///
+/// This is not a passive read. With [`WorkingSetMethod::Referenced`] the referenced bits of all
+/// the pages of the target process are cleared, which affects the kernel's page reclaim
+/// decisions for it. With [`WorkingSetMethod::IdlePage`] the pages of the target are marked idle.
+/// The calling thread sleeps for `interval`.
+///
+/// # Errors
+///
+/// Will return `Err` if the process does not exist, the caller does not have permission to
+/// write its `clear_refs` file (or the idle page bitmap), or its memory information cannot be read
+///
+/// # Examples
+///
+/// ```
+/// use libproc::working_set::{estimate_working_set, WorkingSetMethod};
+/// use std::process;
+/// use std::time::Duration;
+///
+/// let pid = process::id() as i32;
+/// match estimate_working_set(pid, Duration::from_millis(10), WorkingSetMethod::Referenced) {
+///     Ok(wss) => println!("Working set size: {} bytes", wss.total),
+///     Err(err) => eprintln!("Error: {}", err)
+/// }
+/// ```
+pub fn estimate_working_set(
+    pid: i32,
+    interval: Duration,
+    method: WorkingSetMethod,
+) -> Result<WorkingSetSize, String> {
+    let regions = match method {
+        WorkingSetMethod::Referenced => referenced_working_set(pid, interval)?,
+        WorkingSetMethod::IdlePage => idle_page_working_set(pid, interval)?,
+    };
+
+    Ok(WorkingSetSize::new(method, interval, regions))
+}
+
+fn referenced_working_set(pid: i32, interval: Duration) -> Result<Vec<RegionWorkingSet>, String> {
+    let clear_refs = format!("/proc/{pid}/clear_refs");
+    fs::write(&clear_refs, CLEAR_ALL_REFS)
+        .map_err(|e| format!("Could not write to '{clear_refs}': {e}"))?;
+
+    thread::sleep(interval);
+
+    let smaps = format!("/proc/{pid}/smaps");
+    let file = File::open(&smaps).map_err(|e| format!("Could not open '{smaps}': {e}"))?;
+    parse_smaps_referenced(BufReader::new(file))
+}
+
+/// Parse the contents of a `/proc/<pid>/smaps` file, returning the number of bytes
+/// `Referenced` in each region
+///
+/// # Errors
+///
+/// Will return `Err` if the contents cannot be read or a `Referenced` value cannot be parsed
+pub fn parse_smaps_referenced<R: BufRead>(reader: R) -> Result<Vec<RegionWorkingSet>, String> {
+    let mut regions: Vec<RegionWorkingSet> = Vec::new();
+
+    for line in reader.lines() {
+        let line = line.map_err(|e| format!("Could not read smaps: {e}"))?;
+        if let Some(value) = line.strip_prefix("Referenced:") {
+            if let Some(current) = regions.last_mut() {
+                current.bytes = parse_memory_string(value)?;
+            }
+        } else if let Some(region) = MemoryRegion::parse(&line) {
+            regions.push(RegionWorkingSet { region, bytes: 0 });
+        }
+    }
+
+    Ok(regions)
+}
+
+fn idle_page_working_set(pid: i32, interval: Duration) -> Result<Vec<RegionWorkingSet>, String> {
+    let bitmap = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(IDLE_PAGE_BITMAP)
+        .map_err(|e| format!("Could not open '{IDLE_PAGE_BITMAP}': {e}"))?;
+
+    // Find the page frames backing each region, as only those can be tracked
+    let mut region_pfns = Vec::new();
+    for region in memory_regions(pid)? {
+        let mut pfns = Vec::new();
+        // Some special regions, such as [vsyscall], cannot be read from pagemap
+        if let Ok(pages) = region_pages(pid, &region) {
+            pfns.extend(pages.flatten().filter_map(|page| page.pfn));
+        }
+        region_pfns.push((region, pfns));
+    }
+
+    if region_pfns.iter().all(|(_, pfns)| pfns.is_empty()) {
+        return Err("No page frame numbers visible, root privileges are required".into());
+    }
+
+    // The bitmap must be accessed in 64 bit words, one bit per page frame
+    let mut words = BTreeMap::<u64, u64>::new();
+    for pfn in region_pfns.iter().flat_map(|(_, pfns)| pfns) {
+        *words.entry(pfn / 64).or_default() |= 1 << (pfn % 64);
+    }
+    for (word, bits) in &words {
+        bitmap
+            .write_all_at(&bits.to_ne_bytes(), word * 8)
+            .map_err(|e| format!("Could not write to '{IDLE_PAGE_BITMAP}': {e}"))?;
+    }
+
+    thread::sleep(interval);
+
+    let mut idle = BTreeMap::<u64, u64>::new();
+    for word in words.keys() {
+        let mut bytes = [0u8; 8];
+        bitmap
+            .read_exact_at(&mut bytes, word * 8)
+            .map_err(|e| format!("Could not read '{IDLE_PAGE_BITMAP}': {e}"))?;
+        idle.insert(*word, u64::from_ne_bytes(bytes));
+    }
+
+    let page_size = page_size();
+    Ok(region_pfns
+        .into_iter()
+        .map(|(region, pfns)| {
+            let accessed = pfns
+                .iter()
+                .filter(|pfn| idle.get(&(*pfn / 64)).unwrap_or(&0) & (1 << (*pfn % 64)) == 0)
+                .count() as u64;
+            RegionWorkingSet {
+                region,
+                bytes: accessed * page_size,
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+#[allow(clippy::cast_possible_wrap)]
+mod test {
+    use super::{
+        estimate_working_set, idle_page_tracking_available, parse_smaps_referenced,
+        WorkingSetMethod,
+    };
+    use crate::libproc::proc_pid::am_root;
+    use std::process;
+    use std::time::Duration;
+
+    const SMAPS: &str = "\
+55f39f717000-55f39f719000 r--p 00000000 fe:00 317783                     /usr/bin/head
+Size:                  8 kB
+Rss:                   8 kB
+Referenced:            8 kB
+Anonymous:             0 kB
+7ffd1c3c1000-7ffd1c3e2000 rw-p 00000000 00:00 0                          [stack]
+Size:                132 kB
+Rss:                  16 kB
+Referenced:           12 kB
+VmFlags: rd wr mr mw me gd ac
+";
+
+    #[test]
+    fn parse_referenced() {
+        let regions = parse_smaps_referenced(SMAPS.as_bytes()).expect("Could not parse smaps");
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].bytes, 8 * 1024);
+        assert_eq!(regions[1].region.pathname.as_deref(), Some("[stack]"));
+        assert_eq!(regions[1].bytes, 12 * 1024);
+    }
+
+    #[test]
+    fn parse_invalid_referenced() {
+        let smaps = "7ffd1c3c1000-7ffd1c3e2000 rw-p 00000000 00:00 0\nReferenced: lots\n";
+        assert!(parse_smaps_referenced(smaps.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn referenced_working_set_of_self() {
+        let wss = estimate_working_set(
+            process::id() as i32,
+            Duration::from_millis(10),
+            WorkingSetMethod::Referenced,
+        )
+        .expect("estimate_working_set() failed");
+        assert!(wss.total > 0, "Working set size reports 0");
+        assert_eq!(wss.total, wss.regions.iter().map(|r| r.bytes).sum());
+    }
+
+    #[test]
+    fn idle_page_working_set_of_self() {
+        if am_root() && idle_page_tracking_available() {
+            let wss = estimate_working_set(
+                process::id() as i32,
+                Duration::from_millis(10),
+                WorkingSetMethod::IdlePage,
+            )
+            .expect("estimate_working_set() failed");
+            assert!(wss.total > 0, "Working set size reports 0");
+        } else {
+            println!("test skipped as it needs root and idle page tracking");
+        }
+    }
+}
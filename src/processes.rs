@@ -1,14 +1,29 @@
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+use std::cmp::Reverse;
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+use std::collections::BTreeMap;
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+use std::convert::TryFrom;
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+use std::fs;
 use std::io;
-#[cfg(target_os = "macos")]
 use std::path::Path;
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+use std::path::PathBuf;
 
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+use crate::libproc::proc_pid::{self, Namespace};
 use crate::libproc::sys::listpids;
 #[cfg(target_os = "macos")]
 use crate::libproc::sys::listpidspath;
 
 /// `ProcFilter` is used to filter process ids.
 /// See [`pids_by_type`] and `pids_by_type_and_path` (macOS only) for details.
+///
+/// Some filters are only available on some platforms, and more may be added, so matches on a
+/// `ProcFilter` outside this crate need a wildcard arm.
 #[derive(Copy, Clone)]
+#[non_exhaustive]
 pub enum ProcFilter {
     /// All processes
     All,
@@ -37,6 +52,23 @@ pub enum ProcFilter {
         /// List PIDs of processes that are children of this specific process.
         ppid: u32,
     },
+    /// Filter by namespace (Linux only)
+    #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+    ByNamespace {
+        /// The type of namespace to compare
+        namespace: Namespace,
+        /// List PIDs of processes in the same `namespace` as this specific process.
+        pid: u32,
+    },
+    /// Filter by cgroup (Linux only)
+    #[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+    ByCgroup {
+        /// List PIDs of processes in this cgroup or cgroups below it, in any hierarchy, e.g.
+        /// "/system.slice". Paths are compared by component, so "/system.slice/foo" does not
+        /// match a process in "/system.slice/foobar". The path is `'static` so that
+        /// `ProcFilter` stays `Copy`.
+        prefix: &'static Path,
+    },
 }
 
 /// Returns the PIDs of active processes that match the given [`ProcFilter`] filter.
@@ -75,6 +107,221 @@ pub fn pids_by_type(filter: ProcFilter) -> io::Result<Vec<u32>> {
     listpids(filter)
 }
 
+/// Returns the PIDs of active processes that match the given [`ProcFilter`] filter, together with
+/// their OOM killer score, sorted so that the process the kernel would kill first when out of
+/// memory comes first.
+///
+/// Processes that exit while the list is being built are left out.
+///
+/// # Errors
+///
+/// Will return an error if the pids matching the filter cannot be listed for some reason
+///
+/// # Examples
+///
+/// ```
+/// use libproc::processes;
+///
+/// if let Ok(pids) = processes::pids_by_oom_score(processes::ProcFilter::All) {
+///     if let Some((pid, score)) = pids.first() {
+///         println!("Process {} would be the first OOM killer victim, with score {}", pid, score);
+///     }
+/// }
+/// ```
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+pub fn pids_by_oom_score(filter: ProcFilter) -> io::Result<Vec<(u32, u32)>> {
+    let mut scores: Vec<(u32, u32)> = pids_by_type(filter)?
+        .into_iter()
+        .filter_map(|pid| {
+            let score = proc_pid::oom_score(i32::try_from(pid).ok()?).ok()?;
+            Some((pid, score))
+        })
+        .collect();
+    scores
+        .sort_by(|(pid_a, score_a), (pid_b, score_b)| score_b.cmp(score_a).then(pid_a.cmp(pid_b)));
+
+    Ok(scores)
+}
+
+/// Returns the PIDs of all active processes grouped by the inode of their `namespace`, which
+/// identifies it. Processes whose namespaces the caller cannot inspect are left out.
+///
+/// # Errors
+///
+/// Will return an error if the processes on the system cannot be listed
+///
+/// # Examples
+///
+/// ```
+/// use libproc::libproc::proc_pid::Namespace;
+/// use libproc::processes;
+///
+/// let containers = processes::pids_by_namespace(Namespace::Pid).expect("Could not list pids");
+/// for (inode, pids) in containers {
+///     println!("Pid namespace {}: {} processes", inode, pids.len());
+/// }
+/// ```
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+pub fn pids_by_namespace(namespace: Namespace) -> io::Result<BTreeMap<u64, Vec<u32>>> {
+    let mut namespaces: BTreeMap<u64, Vec<u32>> = BTreeMap::new();
+    for pid in pids_by_type(ProcFilter::All)? {
+        let Ok(signed_pid) = i32::try_from(pid) else {
+            continue;
+        };
+        // Processes may exit while being scanned
+        if let Ok(inode) = proc_pid::namespace(signed_pid, namespace) {
+            namespaces.entry(inode).or_default().push(pid);
+        }
+    }
+    Ok(namespaces)
+}
+
+/// Returns the PIDs of the processes in the cgroup `cgroup`, read from its `cgroup.procs` file
+/// in the cgroup filesystem mounted at `root`. Processes in cgroups below it are not included,
+/// use [`ProcFilter::ByCgroup`] for those.
+///
+/// Cgroup paths are relative to the root of their hierarchy, as returned by
+/// [`cgroups`][crate::libproc::proc_pid::cgroups], e.g. "/system.slice/cron.service". The
+/// unified hierarchy is usually mounted at [`CGROUP_ROOT`][crate::libproc::cgroup::CGROUP_ROOT],
+/// see [`CgroupFs::find`][crate::libproc::cgroup::CgroupFs::find] to locate it.
+///
+/// # Errors
+///
+/// Will return an error if `cgroup.procs` cannot be read, e.g. because `cgroup` does not exist,
+/// or contains something other than pids
+///
+/// # Examples
+///
+/// ```
+/// use libproc::libproc::cgroup::CGROUP_ROOT;
+/// use libproc::processes;
+/// use std::path::Path;
+///
+/// let cron = Path::new("/system.slice/cron.service");
+/// if let Ok(pids) = processes::pids_in_cgroup(Path::new(CGROUP_ROOT), cron) {
+///     println!("The cron service has {} processes", pids.len());
+/// }
+/// ```
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+pub fn pids_in_cgroup(root: &Path, cgroup: &Path) -> io::Result<Vec<u32>> {
+    let filename = root
+        .join(cgroup.strip_prefix("/").unwrap_or(cgroup))
+        .join("cgroup.procs");
+    fs::read_to_string(&filename)?
+        .lines()
+        .map(|line| {
+            line.trim().parse().map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid pid '{line}' in '{}'", filename.display()),
+                )
+            })
+        })
+        .collect()
+}
+
+/// A file that has been deleted but is still held open by one or more processes, so the space it
+/// uses on its filesystem has not been freed
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeletedFile {
+    /// Path the file had before it was deleted
+    pub path: PathBuf,
+    /// Inode number of the file
+    pub inode: u64,
+    /// Size of the file in bytes
+    pub size: u64,
+    /// Space allocated to the file on its filesystem, in bytes
+    pub allocated: u64,
+    /// The (pid, fd) pairs that hold the file open
+    pub holders: Vec<(u32, i32)>,
+}
+
+/// The space held on one filesystem by deleted files that are still open
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeletedFilesUsage {
+    /// Device number of the filesystem
+    pub dev: u64,
+    /// Total space allocated to the deleted files on this filesystem, in bytes
+    pub allocated: u64,
+    /// The deleted files
+    pub files: Vec<DeletedFile>,
+}
+
+/// Find the files on each filesystem that have been deleted but are still held open by some
+/// process, and so still take up space. A process holding a deleted log file is a common reason
+/// for a filesystem being full when `du` says it is not.
+///
+/// Only the processes whose file descriptors the caller has permission to read are checked.
+/// Filesystems are returned with those where most space is held first.
+///
+/// # Errors
+///
+/// Will return an error if the list of processes cannot be read
+///
+/// # Examples
+///
+/// ```
+/// use libproc::processes;
+///
+/// for usage in processes::deleted_open_files().expect("Could not find deleted open files") {
+///     println!("Device {}: {} bytes held by {} deleted files", usage.dev, usage.allocated,
+///              usage.files.len());
+/// }
+/// ```
+#[cfg(any(target_os = "linux", target_os = "redox", target_os = "android"))]
+pub fn deleted_open_files() -> io::Result<Vec<DeletedFilesUsage>> {
+    use std::os::unix::fs::MetadataExt;
+
+    // Keyed on (dev, inode) as the same file may be held by many fds and processes
+    let mut deleted: BTreeMap<(u64, u64), DeletedFile> = BTreeMap::new();
+    for pid in pids_by_type(ProcFilter::All)? {
+        let Ok(signed_pid) = i32::try_from(pid) else {
+            continue;
+        };
+        let Ok(files) = proc_pid::open_files(signed_pid) else {
+            continue;
+        };
+        for file in files.into_iter().filter(|f| f.deleted) {
+            let fd = file.fd;
+            // stat() on the fd link follows it to the (deleted) file itself
+            let Ok(metadata) = fs::metadata(format!("/proc/{pid}/fd/{fd}")) else {
+                continue;
+            };
+            deleted
+                .entry((metadata.dev(), metadata.ino()))
+                .or_insert_with(|| DeletedFile {
+                    path: file.path,
+                    inode: metadata.ino(),
+                    size: metadata.size(),
+                    allocated: metadata.blocks() * 512,
+                    holders: Vec::new(),
+                })
+                .holders
+                .push((pid, fd));
+        }
+    }
+
+    let mut usages: Vec<DeletedFilesUsage> = Vec::new();
+    for ((dev, _), file) in deleted {
+        match usages.iter_mut().find(|usage| usage.dev == dev) {
+            Some(usage) => {
+                usage.allocated += file.allocated;
+                usage.files.push(file);
+            }
+            None => usages.push(DeletedFilesUsage {
+                dev,
+                allocated: file.allocated,
+                files: vec![file],
+            }),
+        }
+    }
+    usages.sort_by_key(|usage| Reverse(usage.allocated));
+
+    Ok(usages)
+}
+
 /// Returns the PIDs of active processes that reference an open file with the given path or volume.
 ///
 ///Filter for pids with or without files opened with the `O_EVTONLY` flag.
@@ -182,3 +429,84 @@ pub fn pids_by_type_and_path(
 ) -> io::Result<Vec<u32>> {
     listpidspath(filter, path, is_volume, exclude_event_only)
 }
+
+#[cfg(all(
+    test,
+    any(target_os = "linux", target_os = "redox", target_os = "android")
+))]
+// Don't worry about wrapping in tests
+#[allow(clippy::cast_possible_wrap)]
+mod test {
+    use std::path::Path;
+
+    #[test]
+    fn pids_by_oom_score_sorted() {
+        let scores =
+            super::pids_by_oom_score(super::ProcFilter::All).expect("pids_by_oom_score() failed");
+        assert!(!scores.is_empty());
+        assert!(scores.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+    }
+
+    #[test]
+    fn deleted_open_files_found() {
+        use std::io::Write;
+
+        let mut file = tempfile::tempfile().expect("Could not create temp file");
+        file.write_all(&[0; 8192])
+            .expect("Could not write to temp file");
+        file.sync_all().expect("Could not sync temp file");
+
+        let pid = std::process::id();
+        let usages = super::deleted_open_files().expect("deleted_open_files() failed");
+        let held = usages
+            .iter()
+            .flat_map(|usage| &usage.files)
+            .find(|f| f.holders.iter().any(|(holder, _)| *holder == pid) && f.size == 8192)
+            .expect("Deleted temp file not found");
+        assert!(held.path.is_absolute());
+    }
+
+    #[test]
+    fn pids_in_fake_cgroup() {
+        let root = tempfile::tempdir().expect("Could not create temp dir");
+        let service = root.path().join("system.slice").join("nginx.service");
+        std::fs::create_dir_all(&service).expect("Could not create cgroup dir");
+        std::fs::write(service.join("cgroup.procs"), "4120\n4121\n4122\n")
+            .expect("Could not write cgroup.procs");
+        std::fs::write(root.path().join("cgroup.procs"), "").expect("Could not write cgroup.procs");
+
+        let nginx = Path::new("/system.slice/nginx.service");
+        assert_eq!(
+            super::pids_in_cgroup(root.path(), nginx).expect("pids_in_cgroup() failed"),
+            vec![4120, 4121, 4122]
+        );
+        assert!(super::pids_in_cgroup(root.path(), Path::new("/"))
+            .expect("pids_in_cgroup() failed")
+            .is_empty());
+        assert!(super::pids_in_cgroup(root.path(), Path::new("/missing.slice")).is_err());
+
+        std::fs::write(service.join("cgroup.procs"), "4120\nnot a pid\n")
+            .expect("Could not write cgroup.procs");
+        assert!(super::pids_in_cgroup(root.path(), nginx).is_err());
+    }
+
+    #[test]
+    fn pids_by_cgroup_filter() {
+        use crate::libproc::proc_pid;
+
+        let pid = std::process::id();
+        let cgroups = proc_pid::cgroups(pid as i32).expect("cgroups() failed");
+        let own = cgroups.first().expect("No cgroups found");
+        // The filter takes a `&'static Path` so that it stays `Copy`
+        let prefix: &'static Path = Box::leak(own.path.clone().into_boxed_path());
+        assert!(super::pids_by_type(super::ProcFilter::ByCgroup { prefix })
+            .expect("pids_by_type() failed")
+            .contains(&pid));
+
+        let prefix: &'static Path =
+            Box::leak(own.path.join("libproc-missing-cgroup").into_boxed_path());
+        assert!(!super::pids_by_type(super::ProcFilter::ByCgroup { prefix })
+            .expect("pids_by_type() failed")
+            .contains(&pid));
+    }
+}